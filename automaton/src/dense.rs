@@ -0,0 +1,242 @@
+use crate::{Evolve, Rules, Topology};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// automaton over a bounded topology with each cell's neighbours resolved once up
+// front; the state lives in two flat buffers and only cells that count a changed
+// cell among their neighbours are evaluated on the following step
+pub struct DenseAutomaton<C> {
+    cells: Vec<C>,
+    index: HashMap<C, usize>,
+    neighbor_offsets: Vec<usize>,
+    neighbor_ids: Vec<u32>,
+    // the reverse of the neighbour lists, as neighbourhoods needn't be symmetric
+    watcher_offsets: Vec<usize>,
+    watcher_ids: Vec<u32>,
+    rules: Rules,
+    state: Vec<bool>,
+    next: Vec<bool>,
+    dirty: Vec<usize>,
+    queued: Vec<bool>,
+}
+
+impl<C: Copy + Eq + Hash> DenseAutomaton<C> {
+    pub fn new<T, I>(topology: &T, rules: Rules, alive: I) -> Self
+    where
+        T: Topology<Cell = C>,
+        I: IntoIterator<Item = C>,
+    {
+        let cells = topology
+            .cells()
+            .expect("a dense automaton requires a bounded topology");
+        assert!(
+            cells.len() <= u32::MAX as usize,
+            "a dense automaton holds at most {} cells",
+            u32::MAX
+        );
+        let index = cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (*cell, i))
+            .collect::<HashMap<_, _>>();
+        let mut neighbor_offsets = Vec::with_capacity(cells.len() + 1);
+        let mut neighbor_ids = Vec::new();
+        neighbor_offsets.push(0);
+        for cell in cells.iter() {
+            neighbor_ids.extend(
+                topology
+                    .neighbors(cell)
+                    .iter()
+                    .filter_map(|n| index.get(n))
+                    .map(|&i| i as u32),
+            );
+            neighbor_offsets.push(neighbor_ids.len());
+        }
+        let mut watcher_offsets = vec![0; cells.len() + 1];
+        for &n in neighbor_ids.iter() {
+            watcher_offsets[n as usize + 1] += 1;
+        }
+        for i in 0..cells.len() {
+            watcher_offsets[i + 1] += watcher_offsets[i];
+        }
+        let mut watcher_ids = vec![0; neighbor_ids.len()];
+        let mut filled = watcher_offsets.clone();
+        for i in 0..cells.len() {
+            for &n in neighbor_ids[neighbor_offsets[i]..neighbor_offsets[i + 1]].iter() {
+                watcher_ids[filled[n as usize]] = i as u32;
+                filled[n as usize] += 1;
+            }
+        }
+        let mut state = vec![false; cells.len()];
+        for cell in alive {
+            let i = index
+                .get(&cell)
+                .expect("initial live cell outside the topology");
+            state[*i] = true;
+        }
+        Self {
+            next: state.clone(),
+            dirty: (0..cells.len()).collect(),
+            queued: vec![true; cells.len()],
+            cells,
+            index,
+            neighbor_offsets,
+            neighbor_ids,
+            watcher_offsets,
+            watcher_ids,
+            rules,
+            state,
+        }
+    }
+
+    fn neighbors(&self, i: usize) -> &[u32] {
+        &self.neighbor_ids[self.neighbor_offsets[i]..self.neighbor_offsets[i + 1]]
+    }
+
+    pub fn is_alive(&self, cell: &C) -> bool {
+        self.index.get(cell).is_some_and(|&i| self.state[i])
+    }
+
+    pub fn alive(&self) -> impl Iterator<Item = &C> {
+        self.cells
+            .iter()
+            .zip(self.state.iter())
+            .filter(|(_, &alive)| alive)
+            .map(|(cell, _)| cell)
+    }
+
+    // liveness of every cell, in the order of the topology's cells
+    pub fn state(&self) -> &[bool] {
+        &self.state
+    }
+
+    pub fn population(&self) -> usize {
+        self.state.iter().filter(|&&alive| alive).count()
+    }
+}
+
+impl<C: Copy + Eq + Hash> Evolve for DenseAutomaton<C> {
+    fn step(&mut self) -> bool {
+        let mut changed = Vec::new();
+        for &i in self.dirty.iter() {
+            self.queued[i] = false;
+            let count = self
+                .neighbors(i)
+                .iter()
+                .filter(|&&n| self.state[n as usize])
+                .count();
+            let alive = if self.state[i] {
+                self.rules.survives_on(count)
+            } else {
+                self.rules.births_on(count)
+            };
+            if alive != self.state[i] {
+                self.next[i] = alive;
+                changed.push(i);
+            }
+        }
+
+        // both buffers only differ in the changed cells, so they are synced up again
+        // after swapping to keep the untouched cells valid in either of them
+        std::mem::swap(&mut self.state, &mut self.next);
+        self.dirty.clear();
+        for &i in changed.iter() {
+            self.next[i] = self.state[i];
+            let watchers = &self.watcher_ids[self.watcher_offsets[i]..self.watcher_offsets[i + 1]];
+            for &n in std::iter::once(&(i as u32)).chain(watchers) {
+                if !self.queued[n as usize] {
+                    self.queued[n as usize] = true;
+                    self.dirty.push(n as usize);
+                }
+            }
+        }
+        !changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Automaton, Grid, Neighborhood};
+    use std::collections::HashSet;
+
+    #[test]
+    fn matches_sparse_automaton_test() {
+        // pseudo random seat layout, roughly a fifth of it being floor
+        let mut seed = 7u64;
+        let cells = (0..40)
+            .map(|_| {
+                (0..50)
+                    .map(|_| {
+                        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                        !(seed >> 33).is_multiple_of(5)
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<bool>>>();
+        for &(neighborhood, rules) in [
+            (Neighborhood::Adjacent, "B0/S0123"),
+            (Neighborhood::LineOfSight, "B0/S01234"),
+        ]
+        .iter()
+        {
+            let grid = Grid::new(cells.clone(), neighborhood);
+            let mut dense = DenseAutomaton::new(&grid, rules.parse().unwrap(), vec![]);
+            let mut sparse = Automaton::new(grid, rules.parse().unwrap(), vec![]);
+            for _ in 0..100 {
+                let changed = sparse.step();
+                assert_eq!(dense.step(), changed);
+                assert_eq!(
+                    dense.alive().copied().collect::<HashSet<_>>(),
+                    *sparse.alive()
+                );
+                if !changed {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "initial live cell outside the topology")]
+    fn outside_cell_test() {
+        let grid = Grid::new(vec![vec![true, true]], Neighborhood::Adjacent);
+        DenseAutomaton::new(&grid, "B3/S23".parse().unwrap(), vec![(0, 2)]);
+    }
+
+    // every cell only sees the one to its right, so a change travels leftwards
+    struct RightOnly(usize);
+
+    impl Topology for RightOnly {
+        type Cell = usize;
+
+        fn neighbors(&self, cell: &usize) -> Vec<usize> {
+            vec![cell + 1]
+        }
+
+        fn cells(&self) -> Option<Vec<usize>> {
+            Some((0..self.0).collect())
+        }
+    }
+
+    #[test]
+    fn asymmetric_neighborhood_test() {
+        let rules: Rules = "B1/S".parse().unwrap();
+        let mut dense = DenseAutomaton::new(&RightOnly(6), rules.clone(), vec![5]);
+        let mut expected = vec![false, false, false, false, false, true];
+        for _ in 0..8 {
+            expected = (0..6)
+                .map(|i| {
+                    let count = usize::from(expected.get(i + 1) == Some(&true));
+                    if expected[i] {
+                        rules.survives_on(count)
+                    } else {
+                        rules.births_on(count)
+                    }
+                })
+                .collect();
+            dense.step();
+            assert_eq!(dense.state(), expected);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+mod dense;
+mod rules;
+mod topology;
+
+pub use dense::DenseAutomaton;
+pub use rules::Rules;
+pub use topology::{Grid, Hex, Lattice, Neighborhood, Topology};
+
+use std::collections::{HashMap, HashSet};
+
+pub trait Evolve {
+    // advances one generation, returning whether any cell changed
+    fn step(&mut self) -> bool;
+
+    // returns the number of generations it took for the state to stop changing,
+    // None if it still changes after `max_generations` as oscillators always do
+    fn run_until_stable(&mut self, max_generations: usize) -> Option<usize> {
+        (0..=max_generations).find(|_| !self.step())
+    }
+}
+
+pub struct Automaton<T: Topology> {
+    topology: T,
+    rules: Rules,
+    alive: HashSet<T::Cell>,
+}
+
+impl<T: Topology> Automaton<T> {
+    pub fn new<I: IntoIterator<Item = T::Cell>>(topology: T, rules: Rules, alive: I) -> Self {
+        assert!(
+            !rules.births_on(0) || topology.cells().is_some(),
+            "rules with birth on 0 neighbours require a bounded topology"
+        );
+        Self {
+            topology,
+            rules,
+            alive: alive.into_iter().collect(),
+        }
+    }
+
+    pub fn topology(&self) -> &T {
+        &self.topology
+    }
+
+    pub fn alive(&self) -> &HashSet<T::Cell> {
+        &self.alive
+    }
+
+    pub fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        (0..generations).for_each(|_| {
+            self.step();
+        });
+    }
+}
+
+impl<T: Topology> Evolve for Automaton<T> {
+    fn step(&mut self) -> bool {
+        let counts = self.alive.iter().fold(HashMap::new(), |mut acc, cell| {
+            self.topology.neighbors(cell).into_iter().for_each(|n| {
+                *acc.entry(n).or_insert(0) += 1;
+            });
+            acc
+        });
+        let candidates = match self.topology.cells() {
+            Some(cells) => cells,
+            None => counts.keys().chain(self.alive.iter()).copied().collect(),
+        };
+        let next = candidates
+            .into_iter()
+            .filter(|cell| {
+                let count = *counts.get(cell).unwrap_or(&0);
+                if self.alive.contains(cell) {
+                    self.rules.survives_on(count)
+                } else {
+                    self.rules.births_on(count)
+                }
+            })
+            .collect::<HashSet<_>>();
+        let changed = next != self.alive;
+        self.alive = next;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blinker_test() {
+        let alive = vec![[0, -1], [0, 0], [0, 1]];
+        let mut automaton = Automaton::new(Lattice::<2>::new(), Rules::life(), alive.clone());
+        automaton.step();
+        let expected = vec![[-1, 0], [0, 0], [1, 0]].into_iter().collect();
+        assert_eq!(automaton.alive(), &expected);
+        automaton.step();
+        assert_eq!(automaton.alive(), &alive.into_iter().collect());
+        assert_eq!(automaton.run_until_stable(100), None);
+    }
+
+    #[test]
+    fn bounded_grid_test() {
+        let grid = Grid::new(
+            vec![vec![true, false, true], vec![true, true, true]],
+            Neighborhood::LineOfSight,
+        );
+        let mut automaton = Automaton::new(grid, "B0/S0123".parse().unwrap(), vec![]);
+        assert!(automaton.step());
+        assert_eq!(automaton.population(), 5);
+        assert_eq!(automaton.run_until_stable(10), Some(1));
+        assert_eq!(automaton.population(), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn unbounded_birth_on_zero_test() {
+        Automaton::new(Hex::new(), Rules::new(&[0], &[]), vec![]);
+    }
+}
@@ -0,0 +1,81 @@
+use std::str::FromStr;
+
+// birth/survival rules in the usual life-like notation, e.g. "B3/S23" for Conway's
+// game of life; counts above 9 can be listed with commas, e.g. "B3/S2,3,10"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rules {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl Rules {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Self {
+        Self {
+            birth: birth.to_vec(),
+            survival: survival.to_vec(),
+        }
+    }
+
+    pub fn life() -> Self {
+        Self::new(&[3], &[2, 3])
+    }
+
+    pub fn births_on(&self, count: usize) -> bool {
+        self.birth.contains(&count)
+    }
+
+    pub fn survives_on(&self, count: usize) -> bool {
+        self.survival.contains(&count)
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (birth, survival) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Missing '/' in rules {}", s))?;
+        Ok(Self {
+            birth: parse_counts(birth, 'B')?,
+            survival: parse_counts(survival, 'S')?,
+        })
+    }
+}
+
+fn parse_counts(s: &str, prefix: char) -> Result<Vec<usize>, String> {
+    let counts = s
+        .strip_prefix(prefix)
+        .ok_or_else(|| format!("Expected {} in {}", prefix, s))?;
+    let parse = |count: &str| {
+        count
+            .parse()
+            .map_err(|_| format!("Invalid count {} in {}", count, s))
+    };
+    if counts.contains(',') {
+        counts.split(',').map(parse).collect()
+    } else {
+        counts
+            .chars()
+            .map(|ch| {
+                ch.to_digit(10)
+                    .map(|count| count as usize)
+                    .ok_or_else(|| format!("Invalid count {} in {}", ch, s))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        assert_eq!("B3/S23".parse(), Ok(Rules::life()));
+        assert_eq!("B/S2,13".parse(), Ok(Rules::new(&[], &[2, 13])));
+        assert!("B3S23".parse::<Rules>().is_err());
+        assert!("S23/B3".parse::<Rules>().is_err());
+        assert!("B3é/S2".parse::<Rules>().is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std::hash::Hash;
+
+pub trait Topology {
+    type Cell: Copy + Eq + Hash;
+
+    fn neighbors(&self, cell: &Self::Cell) -> Vec<Self::Cell>;
+
+    // every cell of a bounded topology, all of them being evaluated on each step;
+    // unbounded topologies only evaluate live cells and their neighbours
+    fn cells(&self) -> Option<Vec<Self::Cell>> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Neighborhood {
+    Adjacent,
+    LineOfSight,
+}
+
+const GRID_DIFFS: [(i32, i32); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+// a bounded grid where only the marked positions are cells; with line of sight the
+// neighbours are the first cells seen in each of the 8 directions
+pub struct Grid {
+    cells: Vec<Vec<bool>>,
+    neighborhood: Neighborhood,
+}
+
+impl Grid {
+    pub fn new(cells: Vec<Vec<bool>>, neighborhood: Neighborhood) -> Self {
+        Self {
+            cells,
+            neighborhood,
+        }
+    }
+
+    pub fn is_cell(&self, x: i32, y: i32) -> Option<bool> {
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.cells.get(x as usize)?.get(y as usize).copied()
+    }
+}
+
+impl Topology for Grid {
+    type Cell = (usize, usize);
+
+    fn neighbors(&self, &(x, y): &Self::Cell) -> Vec<Self::Cell> {
+        GRID_DIFFS
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let (mut next_x, mut next_y) = (x as i32 + dx, y as i32 + dy);
+                loop {
+                    match self.is_cell(next_x, next_y)? {
+                        true => return Some((next_x as usize, next_y as usize)),
+                        false if self.neighborhood == Neighborhood::Adjacent => return None,
+                        false => {
+                            next_x += dx;
+                            next_y += dy;
+                        }
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn cells(&self) -> Option<Vec<Self::Cell>> {
+        Some(
+            self.cells
+                .iter()
+                .enumerate()
+                .flat_map(|(x, row)| {
+                    row.iter()
+                        .enumerate()
+                        .filter(|(_, &is_cell)| is_cell)
+                        .map(move |(y, _)| (x, y))
+                })
+                .collect(),
+        )
+    }
+}
+
+// unbounded N dimensional lattice where all 3^N - 1 surrounding points are neighbours
+pub struct Lattice<const N: usize> {
+    diffs: Vec<[i32; N]>,
+}
+
+impl<const N: usize> Lattice<N> {
+    pub fn new() -> Self {
+        let diffs = (0..3usize.pow(N as u32))
+            .map(|mut i| {
+                let mut diff = [0; N];
+                diff.iter_mut().for_each(|d| {
+                    *d = (i % 3) as i32 - 1;
+                    i /= 3;
+                });
+                diff
+            })
+            .filter(|diff| diff != &[0; N])
+            .collect();
+        Self { diffs }
+    }
+}
+
+impl<const N: usize> Default for Lattice<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Topology for Lattice<N> {
+    type Cell = [i32; N];
+
+    fn neighbors(&self, cell: &Self::Cell) -> Vec<Self::Cell> {
+        self.diffs
+            .iter()
+            .map(|diff| {
+                let mut res = *cell;
+                res.iter_mut().zip(diff.iter()).for_each(|(a, &b)| *a += b);
+                res
+            })
+            .collect()
+    }
+}
+
+const HEX_DIFFS: [(i32, i32, i32); 6] = [
+    (1, -1, 0),
+    (1, 0, -1),
+    (0, -1, 1),
+    (-1, 1, 0),
+    (0, 1, -1),
+    (-1, 0, 1),
+];
+
+// unbounded hexagonal tiling using cube coordinates
+#[derive(Default)]
+pub struct Hex;
+
+impl Hex {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Topology for Hex {
+    type Cell = (i32, i32, i32);
+
+    fn neighbors(&self, &(x, y, z): &Self::Cell) -> Vec<Self::Cell> {
+        HEX_DIFFS
+            .iter()
+            .map(|&(dx, dy, dz)| (x + dx, y + dy, z + dz))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neighbors_test() {
+        assert_eq!(Lattice::<3>::new().neighbors(&[0, 0, 0]).len(), 26);
+        assert_eq!(Lattice::<4>::new().neighbors(&[0, 0, 0, 0]).len(), 80);
+        assert_eq!(Hex::new().neighbors(&(0, 0, 0)).len(), 6);
+
+        let cells = vec![
+            vec![true, false, false, true],
+            vec![false, true, false, false],
+        ];
+        let adjacent = Grid::new(cells.clone(), Neighborhood::Adjacent);
+        assert_eq!(adjacent.neighbors(&(0, 0)), vec![(1, 1)]);
+        assert_eq!(adjacent.neighbors(&(0, 3)), vec![]);
+        let line_of_sight = Grid::new(cells, Neighborhood::LineOfSight);
+        assert_eq!(line_of_sight.neighbors(&(0, 0)), vec![(0, 3), (1, 1)]);
+    }
+}
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::env;
+use std::fmt;
 use std::fs::File;
 use std::io::prelude::*;
 
@@ -13,6 +15,21 @@ fn main() {
         "part2 solution: {}",
         find_count_within(&rules, "shiny gold")
     );
+
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(outer) = args.first() {
+        let paths = if args.iter().any(|arg| arg == "--all") {
+            find_containment_paths(&rules, outer, "shiny gold")
+        } else {
+            find_containment_path(&rules, outer, "shiny gold")
+                .into_iter()
+                .collect()
+        };
+        for path in paths {
+            println!("{} (x{})", path, path.multiplicity());
+        }
+        print!("{}", find_count_breakdown(&rules, "shiny gold"));
+    }
 }
 
 fn find_possibilities_count(rules: &HashMap<String, Vec<(usize, String)>>, target: &str) -> usize {
@@ -40,6 +57,139 @@ fn find_count_within(rules: &HashMap<String, Vec<(usize, String)>>, target: &str
         .sum()
 }
 
+fn find_containment_path(
+    rules: &HashMap<String, Vec<(usize, String)>>,
+    outer: &str,
+    target: &str,
+) -> Option<ContainmentPath> {
+    if outer == target {
+        return Some(ContainmentPath::new(outer));
+    }
+    rules.get(outer)?.iter().find_map(|(count, child)| {
+        find_containment_path(rules, child, target).map(|path| path.prepend(outer, *count))
+    })
+}
+
+fn find_containment_paths(
+    rules: &HashMap<String, Vec<(usize, String)>>,
+    outer: &str,
+    target: &str,
+) -> Vec<ContainmentPath> {
+    if outer == target {
+        return vec![ContainmentPath::new(outer)];
+    }
+    rules
+        .get(outer)
+        .map(|children| {
+            children
+                .iter()
+                .flat_map(|(count, child)| {
+                    find_containment_paths(rules, child, target)
+                        .into_iter()
+                        .map(move |path| path.prepend(outer, *count))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn find_count_breakdown(
+    rules: &HashMap<String, Vec<(usize, String)>>,
+    target: &str,
+) -> BagBreakdown {
+    let msg = format!("No {} bag", target);
+    let next = rules.get(target).expect(&msg);
+    let contents: Vec<BagBreakdown> = next
+        .iter()
+        .map(|(count, child)| BagBreakdown {
+            count: *count,
+            ..find_count_breakdown(rules, child)
+        })
+        .collect();
+    BagBreakdown {
+        color: target.into(),
+        count: 1,
+        total: contents
+            .iter()
+            .map(|inner| inner.count + inner.count * inner.total)
+            .sum(),
+        contents,
+    }
+}
+
+// Each step holds the color and how many of it fit into the previous step's
+// bag, the outermost bag always having a count of 1.
+#[derive(Debug, Clone, PartialEq)]
+struct ContainmentPath {
+    steps: Vec<(usize, String)>,
+}
+
+impl ContainmentPath {
+    pub fn new(color: &str) -> Self {
+        Self {
+            steps: vec![(1, color.into())],
+        }
+    }
+
+    pub fn multiplicity(&self) -> usize {
+        self.steps.iter().map(|(count, _)| count).product()
+    }
+
+    fn prepend(mut self, color: &str, count: usize) -> Self {
+        self.steps[0].0 = count;
+        self.steps.insert(0, (1, color.into()));
+        self
+    }
+}
+
+impl fmt::Display for ContainmentPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (_, outer) = &self.steps[0];
+        write!(f, "{}", outer)?;
+        for (count, color) in self.steps.iter().skip(1) {
+            write!(f, " -> {} {}", count, color)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct BagBreakdown {
+    color: String,
+    count: usize,
+    // bags held inside one of this bag
+    total: usize,
+    contents: Vec<BagBreakdown>,
+}
+
+impl BagBreakdown {
+    fn fmt_indented(&self, f: &mut fmt::Formatter, depth: usize) -> fmt::Result {
+        writeln!(
+            f,
+            "{:indent$}{} {} = {} + {} * {}",
+            "",
+            self.count,
+            self.color,
+            self.count,
+            self.count,
+            self.total,
+            indent = depth * 2
+        )?;
+        self.contents
+            .iter()
+            .try_for_each(|inner| inner.fmt_indented(f, depth + 1))
+    }
+}
+
+impl fmt::Display for BagBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} = {}", self.color, self.total)?;
+        self.contents
+            .iter()
+            .try_for_each(|inner| inner.fmt_indented(f, 1))
+    }
+}
+
 fn read(filename: &str) -> HashMap<String, Vec<(usize, String)>> {
     let mut file = File::open(filename).expect("File not found");
     let mut content = String::new();
@@ -88,4 +238,33 @@ mod tests {
         let rules = read("test-input2.txt");
         assert_eq!(find_count_within(&rules, "shiny gold"), 126);
     }
+
+    #[test]
+    fn containment_paths_test() {
+        let rules = read("test-input.txt");
+        let path = find_containment_path(&rules, "light red", "shiny gold").unwrap();
+        assert_eq!(
+            path.to_string(),
+            "light red -> 1 bright white -> 1 shiny gold"
+        );
+        let paths = find_containment_paths(&rules, "light red", "shiny gold");
+        assert_eq!(
+            paths.iter().map(|p| p.multiplicity()).collect::<Vec<_>>(),
+            vec![1, 4]
+        );
+        assert!(find_containment_path(&rules, "faded blue", "shiny gold").is_none());
+    }
+
+    #[test]
+    fn count_breakdown_test() {
+        let rules = read("test-input.txt");
+        let breakdown = find_count_breakdown(&rules, "shiny gold");
+        assert_eq!(breakdown.total, 32);
+        assert_eq!(breakdown.contents.len(), 2);
+        assert_eq!(breakdown.contents[1].color, "vibrant plum");
+        assert_eq!(breakdown.contents[1].total, 11);
+        let printed = breakdown.to_string();
+        assert!(printed.starts_with("shiny gold = 32\n"));
+        assert!(printed.contains("\n  2 vibrant plum = 2 + 2 * 11\n"));
+    }
 }
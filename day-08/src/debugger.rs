@@ -0,0 +1,164 @@
+use crate::vm::Instruction;
+use crate::GameConsole;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Breakpoint {
+    Index(i64),
+    Accumulator(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub idx: i64,
+    pub instruction: Instruction,
+    pub acc_before: i64,
+    pub acc_after: i64,
+}
+
+pub struct Debugger<'a> {
+    console: &'a mut GameConsole,
+    breakpoints: Vec<Breakpoint>,
+    trace: Vec<TraceEntry>,
+    // the instruction execution is paused on, run without checking its breakpoint
+    // on the next cont
+    stopped_at: Option<i64>,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn new(console: &'a mut GameConsole) -> Self {
+        Self {
+            console,
+            breakpoints: Vec::new(),
+            trace: Vec::new(),
+            stopped_at: None,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.retain(|bp| *bp != breakpoint);
+    }
+
+    pub fn trace(&self) -> &[TraceEntry] {
+        &self.trace
+    }
+
+    pub fn step(&mut self) -> Option<&TraceEntry> {
+        self.execute()?;
+        self.stopped_at = Some(self.console.cpu.ip);
+        self.trace.last()
+    }
+
+    // runs until a breakpoint is hit, returning it, or until the console halts.
+    // index breakpoints fire before their instruction runs, accumulator ones on the
+    // step that changes it to their value
+    pub fn cont(&mut self) -> Option<Breakpoint> {
+        loop {
+            // nothing fires once the console halts
+            self.console.current()?;
+            let ip = self.console.cpu.ip;
+            if self.stopped_at.take() != Some(ip)
+                && self.breakpoints.contains(&Breakpoint::Index(ip))
+            {
+                self.stopped_at = Some(ip);
+                return Some(Breakpoint::Index(ip));
+            }
+            let (acc_before, acc_after) = self.execute()?;
+            let hit = Breakpoint::Accumulator(acc_after);
+            if acc_before != acc_after && self.breakpoints.contains(&hit) {
+                return Some(hit);
+            }
+        }
+    }
+
+    // runs the current instruction, returning the accumulator before and after it
+    fn execute(&mut self) -> Option<(i64, i64)> {
+        let idx = self.console.cpu.ip;
+        let acc_before = self.console.score();
+        let instruction = self.console.current()?.clone();
+        self.console.step();
+        let acc_after = self.console.score();
+        self.trace.push(TraceEntry {
+            idx,
+            instruction,
+            acc_before,
+            acc_after,
+        });
+        Some((acc_before, acc_after))
+    }
+
+    pub fn repl<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        write!(output, "> ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            let cmd = line.split_whitespace().collect::<Vec<_>>();
+            match cmd[..] {
+                [] => {}
+                ["s"] | ["step"] => match self.step() {
+                    Some(entry) => writeln!(output, "{}", format_entry(entry))?,
+                    None => writeln!(output, "{:?}", self.console.exit_reason().unwrap())?,
+                },
+                ["c"] | ["continue"] => match self.cont() {
+                    Some(bp) => writeln!(output, "hit {:?} at {}", bp, self.console.cpu.ip)?,
+                    None => writeln!(output, "{:?}", self.console.exit_reason().unwrap())?,
+                },
+                ["b", idx] | ["break", idx] => match idx.parse() {
+                    Ok(idx) => self.add_breakpoint(Breakpoint::Index(idx)),
+                    Err(_) => writeln!(output, "invalid index {}", idx)?,
+                },
+                ["ba", acc] | ["break-acc", acc] => match acc.parse() {
+                    Ok(acc) => self.add_breakpoint(Breakpoint::Accumulator(acc)),
+                    Err(_) => writeln!(output, "invalid accumulator value {}", acc)?,
+                },
+                ["d"] | ["delete"] => self.breakpoints.clear(),
+                ["d", idx] | ["delete", idx] => match idx.parse() {
+                    Ok(idx) => self.remove_breakpoint(Breakpoint::Index(idx)),
+                    Err(_) => writeln!(output, "invalid index {}", idx)?,
+                },
+                ["da", acc] | ["delete-acc", acc] => match acc.parse() {
+                    Ok(acc) => self.remove_breakpoint(Breakpoint::Accumulator(acc)),
+                    Err(_) => writeln!(output, "invalid accumulator value {}", acc)?,
+                },
+                ["p"] | ["print"] => writeln!(
+                    output,
+                    "ip {} registers {:?} next {}",
+                    self.console.cpu.ip,
+                    self.console.cpu.registers,
+                    self.console
+                        .current()
+                        .map_or("-".into(), |instruction| instruction.to_string())
+                )?,
+                ["t"] | ["trace"] => {
+                    for entry in self.trace() {
+                        writeln!(output, "{}", format_entry(entry))?;
+                    }
+                }
+                ["q"] | ["quit"] => return Ok(()),
+                _ => writeln!(
+                    output,
+                    "commands: step, continue, break <idx>, break-acc <value>, delete [<idx>], delete-acc <value>, print, trace, quit"
+                )?,
+            }
+            write!(output, "> ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+fn format_entry(entry: &TraceEntry) -> String {
+    format!(
+        "{:>5}: {:<12} acc {} -> {}",
+        entry.idx,
+        entry.instruction.to_string(),
+        entry.acc_before,
+        entry.acc_after
+    )
+}
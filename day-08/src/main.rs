@@ -1,129 +1,375 @@
+mod debugger;
+mod vm;
+
+use debugger::Debugger;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::prelude::*;
+use std::env;
+use std::fs::read_to_string;
+use std::io;
+use vm::{Cpu, Exec, Instruction, InstructionSet, Operand};
+
+const REGISTERS: usize = 4;
+const IP_BITS: u32 = 32;
 
 fn main() {
-    let mut console = GameConsole::new(read("input.txt"));
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.iter().map(|s| s.as_str()).collect::<Vec<_>>()[..] {
+        ["run", filename] => {
+            let mut console = GameConsole::new(read(filename));
+            let (reason, _) = console.run();
+            println!("{:?} registers {:?}", reason, console.cpu.registers);
+        }
+        ["debug", filename] => {
+            let mut console = GameConsole::new(read(filename));
+            let stdin = io::stdin();
+            Debugger::new(&mut console)
+                .repl(stdin.lock(), io::stdout())
+                .expect("Failed to run debugger");
+        }
+        ["disasm", filename] => {
+            let isa = InstructionSet::extended();
+            print!("{}", isa.disassemble(&read(filename)));
+        }
+        _ => {
+            let mut console = GameConsole::new(read("input.txt"));
 
-    console.run();
-    println!("part1 solution {}", console.score);
-    console.reset();
+            let (_, score) = console.run();
+            println!("part1 solution {}", score);
+            console.reset();
 
-    console.run_til_termination();
-    println!("part2 solution {}", console.score);
+            let (_, score) = console.repair().expect("Failed to repair program");
+            println!("part2 solution {}", score);
+        }
+    }
 }
 
 struct GameConsole {
-    commands: Vec<Op>,
-    score: i32,
-    cmd_idx: i32,
-    ran_cmds: HashSet<i32>,
+    commands: Vec<Instruction>,
+    isa: InstructionSet,
+    cpu: Cpu,
+    ran_cmds: HashSet<i64>,
 }
 
 impl GameConsole {
-    pub fn new(commands: Vec<Op>) -> Self {
+    pub fn new(commands: Vec<Instruction>) -> Self {
+        Self::with_machine(
+            commands,
+            InstructionSet::extended(),
+            Cpu::new(REGISTERS, IP_BITS),
+        )
+    }
+
+    pub fn with_machine(commands: Vec<Instruction>, isa: InstructionSet, cpu: Cpu) -> Self {
         Self {
             commands,
-            score: 0,
-            cmd_idx: 0,
+            isa,
+            cpu,
             ran_cmds: HashSet::new(),
         }
     }
 
-    pub fn run(&mut self) {
-        while !self.ran_cmds.contains(&self.cmd_idx)
-            && (self.cmd_idx as usize) < self.commands.len()
-        {
-            self.ran_cmds.insert(self.cmd_idx);
-            match self.commands[self.cmd_idx as usize] {
-                Op::Acc(val) => {
-                    self.score += val;
-                    self.cmd_idx += 1;
-                }
-                Op::Jmp(val) => self.cmd_idx += val,
-                Op::Nop(_) => self.cmd_idx += 1,
-            }
+    pub fn score(&self) -> i64 {
+        self.cpu.acc()
+    }
+
+    pub fn run(&mut self) -> (ExitReason, i64) {
+        while self.step() {}
+        (self.exit_reason().unwrap(), self.score())
+    }
+
+    pub fn exit_reason(&self) -> Option<ExitReason> {
+        let ip = self.cpu.ip;
+        let len = self.commands.len() as i64;
+        if ip == len {
+            Some(ExitReason::Terminated)
+        } else if ip < 0 || ip > len {
+            Some(ExitReason::JumpOutOfBounds { target: ip })
+        } else if self.ran_cmds.contains(&ip) {
+            Some(ExitReason::InfiniteLoop { at: ip })
+        } else {
+            None
         }
     }
 
-    pub fn run_til_termination(&mut self) {
-        for i in 0..self.commands.len() {
-            if self.switch(i) {
-                self.run();
-                if self.cmd_idx as usize == self.commands.len() {
-                    return;
+    // the instruction about to be executed, or None once the console has halted
+    pub fn current(&self) -> Option<&Instruction> {
+        match self.exit_reason() {
+            Some(_) => None,
+            None => Some(&self.commands[self.cpu.ip as usize]),
+        }
+    }
+
+    pub fn step(&mut self) -> bool {
+        if self.current().is_none() {
+            return false;
+        }
+        let idx = self.cpu.ip;
+        self.ran_cmds.insert(idx);
+        self.isa
+            .execute(&mut self.cpu, &self.commands[idx as usize]);
+        true
+    }
+
+    // flips the single jmp/nop that makes the program terminate, leaving the console
+    // in its terminated state and returning the flipped index with the final score
+    pub fn repair(&mut self) -> Result<(usize, i64), RepairError> {
+        let len = self.commands.len();
+        let edges = (0..len)
+            .map(|i| self.edges(i).ok_or(RepairError::Unsupported(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut preds = vec![Vec::new(); len + 1];
+        for (i, (next, _)) in edges.iter().enumerate() {
+            if let Some(next) = next {
+                preds[*next].push(i);
+            }
+        }
+        let mut terminates = vec![false; len + 1];
+        terminates[len] = true;
+        let mut queue = vec![len];
+        while let Some(curr) = queue.pop() {
+            for &prev in preds[curr].iter() {
+                if !terminates[prev] {
+                    terminates[prev] = true;
+                    queue.push(prev);
                 }
+            }
+        }
+        if terminates[0] {
+            return Err(RepairError::AlreadyTerminates);
+        }
+
+        // any flip reached by the looping path can only lead to termination through
+        // instructions outside of that path, so checking the flipped edge is enough
+        let mut visited = vec![false; len];
+        let mut candidates = Vec::new();
+        let mut curr = Some(0);
+        while let Some(i) = curr.filter(|&i| i < len && !visited[i]) {
+            visited[i] = true;
+            if matches!(edges[i].1, Some(flipped) if terminates[flipped]) {
+                candidates.push(i);
+            }
+            curr = edges[i].0;
+        }
+
+        match candidates[..] {
+            [] => Err(RepairError::NoFix),
+            [i] => {
                 self.switch(i);
                 self.reset();
+                Ok((i, self.run().1))
             }
+            _ => Err(RepairError::Ambiguous(candidates)),
+        }
+    }
+
+    // the successor of instruction `i` as is and when flipped, None meaning it leaves
+    // the program without terminating; the outer None marks a data dependent jump
+    fn edges(&self, i: usize) -> Option<(Option<usize>, Option<usize>)> {
+        let len = self.commands.len() as i64;
+        let target = |offset: &Operand| match offset {
+            Operand::Imm(offset) => Some(
+                (i as i64)
+                    .checked_add(*offset)
+                    .filter(|t| (0..=len).contains(t)),
+            ),
+            Operand::Reg(_) => None,
+        };
+        let next = Some(i + 1);
+        let instruction = &self.commands[i];
+        match (instruction.name.as_str(), self.isa.get(&instruction.name)?) {
+            ("jmp", _) => Some((target(&instruction.args[0])?.map(|t| t as usize), next)),
+            ("nop", _) => Some((next, target(&instruction.args[0])?.map(|t| t as usize))),
+            (_, Exec::Step(_)) => Some((next, None)),
+            (_, Exec::Jump(_)) => None,
         }
     }
 
     pub fn reset(&mut self) {
-        self.cmd_idx = 0;
-        self.score = 0;
+        self.cpu.reset();
         self.ran_cmds.clear();
     }
 
     fn switch(&mut self, i: usize) -> bool {
-        let mut changed = false;
-        self.commands[i] = match self.commands[i] {
-            Op::Jmp(val) => {
-                changed = true;
-                Op::Nop(val)
-            }
-            Op::Nop(val) => {
-                changed = true;
-                Op::Jmp(val)
-            }
-            Op::Acc(val) => Op::Acc(val),
+        let instruction = &mut self.commands[i];
+        let switched = match instruction.name.as_str() {
+            "jmp" => "nop",
+            "nop" => "jmp",
+            _ => return false,
         };
-        changed
+        instruction.name = switched.into();
+        true
     }
 }
 
-enum Op {
-    Acc(i32),
-    Jmp(i32),
-    Nop(i32),
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExitReason {
+    Terminated,
+    InfiniteLoop { at: i64 },
+    JumpOutOfBounds { target: i64 },
 }
 
-fn read(filename: &str) -> Vec<Op> {
-    let mut file = File::open(filename).expect("File not found");
-    let mut content = String::new();
-    file.read_to_string(&mut content)
-        .expect("Failed to read file");
-
-    content
-        .lines()
-        .map(|s| {
-            let values = s.split(' ').collect::<Vec<_>>();
-            let num = values[1].parse().unwrap();
-            match values[0] {
-                "acc" => Op::Acc(num),
-                "jmp" => Op::Jmp(num),
-                "nop" => Op::Nop(num),
-                _ => panic!("unexpected value {}", values[0]),
-            }
-        })
-        .collect()
+#[derive(Debug, PartialEq)]
+enum RepairError {
+    AlreadyTerminates,
+    NoFix,
+    Ambiguous(Vec<usize>),
+    Unsupported(usize),
+}
+
+fn read(filename: &str) -> Vec<Instruction> {
+    let content = read_to_string(filename).expect("Failed to read file");
+    InstructionSet::extended()
+        .assemble(&content, REGISTERS)
+        .unwrap_or_else(|err| panic!("{}: {}", filename, err))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::debugger::{Breakpoint, TraceEntry};
+    use super::vm::Operand;
     use super::*;
 
     #[test]
     fn part1_test() {
         let mut console = GameConsole::new(read("test-input.txt"));
-        console.run();
-        assert_eq!(console.score, 5);
+        assert_eq!(console.run(), (ExitReason::InfiniteLoop { at: 1 }, 5));
     }
 
     #[test]
     fn part2_test() {
         let mut console = GameConsole::new(read("test-input.txt"));
-        console.run_til_termination();
-        assert_eq!(console.score, 8);
+        assert_eq!(console.repair(), Ok((7, 8)));
+        assert_eq!(console.score(), 8);
+    }
+
+    #[test]
+    fn repair_errors_test() {
+        let isa = InstructionSet::extended();
+        let mut console = GameConsole::new(isa.assemble("acc +1\nnop +0", REGISTERS).unwrap());
+        assert_eq!(console.repair(), Err(RepairError::AlreadyTerminates));
+        console = GameConsole::new(isa.assemble("acc +1\njmp -1\njmp -2", REGISTERS).unwrap());
+        assert_eq!(console.repair(), Err(RepairError::NoFix));
+        console = GameConsole::new(isa.assemble("nop +2\njmp +0", REGISTERS).unwrap());
+        assert_eq!(console.repair(), Err(RepairError::Ambiguous(vec![0, 1])));
+        console = GameConsole::new(isa.assemble("jnz a +1\njmp -1", REGISTERS).unwrap());
+        assert_eq!(console.repair(), Err(RepairError::Unsupported(0)));
+        // a jump past i64::MAX leaves the program just like any other far jump
+        let source = "nop +0\njmp +9223372036854775807\njmp -1";
+        console = GameConsole::new(isa.assemble(source, REGISTERS).unwrap());
+        assert_eq!(console.repair(), Err(RepairError::NoFix));
+    }
+
+    #[test]
+    fn out_of_bounds_test() {
+        let isa = InstructionSet::extended();
+        let mut console = GameConsole::new(isa.assemble("acc +2\njmp -3", REGISTERS).unwrap());
+        assert_eq!(
+            console.run(),
+            (ExitReason::JumpOutOfBounds { target: -2 }, 2)
+        );
+        console = GameConsole::new(isa.assemble("jmp +5", REGISTERS).unwrap());
+        assert_eq!(
+            console.run(),
+            (ExitReason::JumpOutOfBounds { target: 5 }, 0)
+        );
+    }
+
+    #[test]
+    fn extended_program_test() {
+        let isa = InstructionSet::extended();
+        let source = "mov b +5\nacc b\njz c +2\nmul a +3\nmul a b ; a * 5";
+        let program = isa.assemble(source, REGISTERS).unwrap();
+        assert_eq!(
+            isa.disassemble(&program),
+            "mov b +5\nacc b\njz c +2\nmul a +3\nmul a b\n"
+        );
+        let mut console = GameConsole::new(program);
+        assert_eq!(console.run(), (ExitReason::Terminated, 25));
+        assert!(isa.assemble("acc +1 +2", REGISTERS).is_err());
+        assert!(isa.assemble("hlt", REGISTERS).is_err());
+        assert!(isa.assemble("mov +5 +3", REGISTERS).is_err());
+        assert!(isa.assemble("acc z", REGISTERS).is_err());
+        assert!(isa.assemble("acc e", REGISTERS).is_err());
+        assert!(isa.assemble("acc d", REGISTERS).is_ok());
+
+        let program = isa
+            .assemble("mov b +3037000500\nmul b b\nsub a +1\nacc +1", REGISTERS)
+            .unwrap();
+        let mut console = GameConsole::new(program);
+        assert_eq!(console.run(), (ExitReason::Terminated, 0));
+        assert_eq!(
+            console.cpu.registers[1],
+            3037000500i64.wrapping_mul(3037000500)
+        );
+    }
+
+    #[test]
+    fn ip_width_test() {
+        let mut cpu = Cpu::new(1, 4);
+        cpu.jump(7);
+        assert_eq!(cpu.ip, 7);
+        cpu.jump(1);
+        assert_eq!(cpu.ip, -8);
+    }
+
+    #[test]
+    fn debugger_test() {
+        let mut console = GameConsole::new(read("test-input.txt"));
+        let mut debugger = Debugger::new(&mut console);
+        debugger.step();
+        assert_eq!(
+            debugger.step(),
+            Some(&TraceEntry {
+                idx: 1,
+                instruction: Instruction::new("acc", vec![Operand::Imm(1)]),
+                acc_before: 0,
+                acc_after: 1,
+            })
+        );
+        debugger.add_breakpoint(Breakpoint::Index(4));
+        debugger.add_breakpoint(Breakpoint::Accumulator(5));
+        assert_eq!(debugger.cont(), Some(Breakpoint::Accumulator(5)));
+        assert_eq!(debugger.cont(), Some(Breakpoint::Index(4)));
+        // the jmp leaves the accumulator at 5, which doesn't hit it again
+        assert_eq!(debugger.cont(), None);
+        let indices = debugger.trace().iter().map(|entry| entry.idx);
+        assert_eq!(indices.collect::<Vec<_>>(), vec![0, 1, 2, 6, 7, 3, 4]);
+
+        let isa = InstructionSet::extended();
+        let program = isa
+            .assemble("acc +2\nnop +0\nacc +3\nacc -3\nnop +0", REGISTERS)
+            .unwrap();
+        let mut console = GameConsole::new(program);
+        let mut debugger = Debugger::new(&mut console);
+        debugger.add_breakpoint(Breakpoint::Accumulator(2));
+        assert_eq!(debugger.cont(), Some(Breakpoint::Accumulator(2)));
+        assert_eq!(debugger.cont(), Some(Breakpoint::Accumulator(2)));
+        assert_eq!(debugger.cont(), None);
+        let indices = debugger.trace().iter().map(|entry| entry.idx);
+        assert_eq!(indices.collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+        // the first instruction stops before running, then gets run on continuing
+        let mut console = GameConsole::new(read("test-input.txt"));
+        let mut debugger = Debugger::new(&mut console);
+        debugger.add_breakpoint(Breakpoint::Index(0));
+        debugger.add_breakpoint(Breakpoint::Index(1));
+        assert_eq!(debugger.cont(), Some(Breakpoint::Index(0)));
+        assert!(debugger.trace().is_empty());
+        assert_eq!(debugger.cont(), Some(Breakpoint::Index(1)));
+        assert_eq!(debugger.cont(), None);
+    }
+
+    #[test]
+    fn repl_test() {
+        let mut console = GameConsole::new(read("test-input.txt"));
+        let mut output = Vec::new();
+        Debugger::new(&mut console)
+            .repl(&b"b 6\nb 3\nd 3\nc\np\nq\ns\n"[..], &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "> > > > hit Index(6) at 6\n> ip 6 registers [1, 0, 0, 0] next acc +1\n> "
+        );
     }
 }
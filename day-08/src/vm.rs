@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::fmt;
+
+pub struct Cpu {
+    pub registers: Vec<i64>,
+    pub ip: i64,
+    ip_bits: u32,
+}
+
+impl Cpu {
+    pub fn new(register_count: usize, ip_bits: u32) -> Self {
+        assert!(register_count > 0, "at least one register is required");
+        assert!(
+            (1..=64).contains(&ip_bits),
+            "unsupported instruction pointer width {}",
+            ip_bits
+        );
+        Self {
+            registers: vec![0; register_count],
+            ip: 0,
+            ip_bits,
+        }
+    }
+
+    // register `a` doubles as the boot code accumulator
+    pub fn acc(&self) -> i64 {
+        self.registers[0]
+    }
+
+    pub fn value(&self, operand: &Operand) -> i64 {
+        match *operand {
+            Operand::Imm(val) => val,
+            Operand::Reg(reg) => *self
+                .registers
+                .get(reg)
+                .unwrap_or_else(|| panic!("No register {}", register_name(reg))),
+        }
+    }
+
+    pub fn register_mut(&mut self, operand: &Operand) -> &mut i64 {
+        match *operand {
+            Operand::Reg(reg) => self
+                .registers
+                .get_mut(reg)
+                .unwrap_or_else(|| panic!("No register {}", register_name(reg))),
+            Operand::Imm(val) => panic!("Cannot write to immediate value {}", val),
+        }
+    }
+
+    // the instruction pointer wraps around like a signed integer of `ip_bits` width
+    pub fn jump(&mut self, offset: i64) {
+        let shift = 64 - self.ip_bits;
+        self.ip = (self.ip.wrapping_add(offset) << shift) >> shift;
+    }
+
+    pub fn reset(&mut self) {
+        self.ip = 0;
+        self.registers.iter_mut().for_each(|reg| *reg = 0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand {
+    Imm(i64),
+    Reg(usize),
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Imm(val) => write!(f, "{:+}", val),
+            Operand::Reg(reg) => write!(f, "{}", register_name(*reg)),
+        }
+    }
+}
+
+fn register_name(reg: usize) -> char {
+    (b'a' + reg as u8) as char
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub name: String,
+    pub args: Vec<Operand>,
+}
+
+impl Instruction {
+    pub fn new(name: &str, args: Vec<Operand>) -> Self {
+        Self {
+            name: name.into(),
+            args,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        for arg in self.args.iter() {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+// what an instruction accepts in each operand position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperandKind {
+    // a register to write to
+    Register,
+    // an immediate value or a register to read from
+    Value,
+}
+
+use OperandKind::{Register, Value};
+
+#[derive(Clone, Copy)]
+pub enum Exec {
+    // executes and moves on to the next instruction
+    Step(fn(&mut Cpu, &[Operand])),
+    // executes and returns the offset to add to the instruction pointer
+    Jump(fn(&mut Cpu, &[Operand]) -> i64),
+}
+
+pub struct InstructionSet {
+    defs: HashMap<String, (&'static [OperandKind], Exec)>,
+}
+
+impl InstructionSet {
+    pub fn new() -> Self {
+        Self {
+            defs: HashMap::new(),
+        }
+    }
+
+    pub fn boot_code() -> Self {
+        let mut isa = Self::new();
+        isa.register(
+            "acc",
+            &[Value],
+            Exec::Step(|cpu, args| cpu.registers[0] = cpu.acc().wrapping_add(cpu.value(&args[0]))),
+        )
+        .register("jmp", &[Value], Exec::Jump(|cpu, args| cpu.value(&args[0])))
+        .register("nop", &[Value], Exec::Step(|_, _| {}));
+        isa
+    }
+
+    pub fn extended() -> Self {
+        let mut isa = Self::boot_code();
+        isa.register(
+            "mov",
+            &[Register, Value],
+            Exec::Step(|cpu, args| *cpu.register_mut(&args[0]) = cpu.value(&args[1])),
+        )
+        .register(
+            "add",
+            &[Register, Value],
+            Exec::Step(|cpu, args| {
+                let val = cpu.value(&args[1]);
+                let reg = cpu.register_mut(&args[0]);
+                *reg = reg.wrapping_add(val);
+            }),
+        )
+        .register(
+            "sub",
+            &[Register, Value],
+            Exec::Step(|cpu, args| {
+                let val = cpu.value(&args[1]);
+                let reg = cpu.register_mut(&args[0]);
+                *reg = reg.wrapping_sub(val);
+            }),
+        )
+        .register(
+            "mul",
+            &[Register, Value],
+            Exec::Step(|cpu, args| {
+                let val = cpu.value(&args[1]);
+                let reg = cpu.register_mut(&args[0]);
+                *reg = reg.wrapping_mul(val);
+            }),
+        )
+        .register(
+            "jz",
+            &[Value, Value],
+            Exec::Jump(|cpu, args| match cpu.value(&args[0]) {
+                0 => cpu.value(&args[1]),
+                _ => 1,
+            }),
+        )
+        .register(
+            "jnz",
+            &[Value, Value],
+            Exec::Jump(|cpu, args| match cpu.value(&args[0]) {
+                0 => 1,
+                _ => cpu.value(&args[1]),
+            }),
+        );
+        isa
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        signature: &'static [OperandKind],
+        exec: Exec,
+    ) -> &mut Self {
+        self.defs.insert(name.into(), (signature, exec));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<Exec> {
+        self.defs.get(name).map(|(_, exec)| *exec)
+    }
+
+    pub fn execute(&self, cpu: &mut Cpu, instruction: &Instruction) {
+        let exec = self
+            .get(&instruction.name)
+            .unwrap_or_else(|| panic!("unexpected instruction {}", instruction.name));
+        let offset = match exec {
+            Exec::Step(f) => {
+                f(cpu, &instruction.args);
+                1
+            }
+            Exec::Jump(f) => f(cpu, &instruction.args),
+        };
+        cpu.jump(offset);
+    }
+
+    // operands are checked against the instruction signatures and the registers
+    // available on a cpu with `registers` registers
+    pub fn assemble(&self, source: &str, registers: usize) -> Result<Vec<Instruction>, String> {
+        source
+            .lines()
+            .enumerate()
+            .map(|(line, s)| (line, s.split(';').next().unwrap().trim()))
+            .filter(|(_, s)| !s.is_empty())
+            .map(|(line, s)| {
+                self.assemble_line(s, registers)
+                    .map_err(|err| format!("line {}: {}", line + 1, err))
+            })
+            .collect()
+    }
+
+    fn assemble_line(&self, s: &str, registers: usize) -> Result<Instruction, String> {
+        let mut tokens = s.split_whitespace();
+        let name = tokens.next().unwrap();
+        let (signature, _) = self
+            .defs
+            .get(name)
+            .ok_or_else(|| format!("unexpected instruction {}", name))?;
+        let args = tokens.map(parse_operand).collect::<Result<Vec<_>, _>>()?;
+        if args.len() != signature.len() {
+            return Err(format!(
+                "{} expects {} operand(s), got {}",
+                name,
+                signature.len(),
+                args.len()
+            ));
+        }
+        for (arg, kind) in args.iter().zip(signature.iter()) {
+            match (arg, kind) {
+                (Operand::Reg(reg), _) if *reg >= registers => {
+                    return Err(format!("no register {}", register_name(*reg)))
+                }
+                (Operand::Imm(_), Register) => {
+                    return Err(format!("{} can't write to immediate value {}", name, arg))
+                }
+                _ => {}
+            }
+        }
+        Ok(Instruction::new(name, args))
+    }
+
+    pub fn disassemble(&self, program: &[Instruction]) -> String {
+        program
+            .iter()
+            .map(|instruction| format!("{}\n", instruction))
+            .collect()
+    }
+}
+
+fn parse_operand(token: &str) -> Result<Operand, String> {
+    match token.as_bytes() {
+        [reg @ b'a'..=b'z'] => Ok(Operand::Reg((reg - b'a') as usize)),
+        _ => token
+            .parse()
+            .map(Operand::Imm)
+            .map_err(|_| format!("invalid operand {}", token)),
+    }
+}
@@ -1,62 +1,133 @@
-use std::cmp::Ordering;
-use std::fs::read_to_string;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::ops::Range;
 
 fn main() {
     let data = read("input.txt");
-    let res = find_first_invalid(&data, 25);
+    let res = find_first_invalid(&data, 25).expect("No invalid number found");
     println!("part1 solution: {}", res);
-    println!("part2 solution: {}", find_encryption_weakness(&data, res));
+    let range = find_encryption_weakness(&data, res).expect("No range sums to the invalid number");
+    let (min, max) = min_max(&data[range]);
+    println!("part2 solution: {}", min + max);
 }
 
-fn find_first_invalid(data: &[usize], length: usize) -> usize {
-    let res = data
-        .windows(length)
-        .enumerate()
-        .find(|(first_pos, chunk)| !two_sum_exists(chunk, data[first_pos + length]));
-    data[res.unwrap().0 + length]
+fn find_first_invalid(data: &[i64], length: usize) -> Option<i64> {
+    XmasValidator::new(data.iter().copied(), length)
+        .next()
+        .map(|(_, value)| value)
 }
 
-fn find_encryption_weakness(data: &[usize], target: usize) -> usize {
-    let (mut low, mut high, mut curr_res) = (0, 0, 0);
-    loop {
-        match curr_res.cmp(&target) {
-            Ordering::Less => {
-                curr_res += data[high];
-                high += 1;
-            }
-            Ordering::Greater => {
-                curr_res -= data[low];
-                low += 1;
-            }
-            Ordering::Equal => {
-                let (mut min, mut max) = (usize::MAX, usize::MIN);
-                (low..high).for_each(|i| {
-                    min = min.min(data[i]);
-                    max = max.max(data[i]);
-                });
-                return min + max;
+// yields (index, value) of every number that isn't a sum of two numbers in the
+// preceding window, keeping a count of all pair sums so each step is O(length)
+struct XmasValidator<I> {
+    numbers: I,
+    idx: usize,
+    preamble: PairSums,
+}
+
+impl<I: Iterator<Item = i64>> XmasValidator<I> {
+    pub fn new(numbers: I, length: usize) -> Self {
+        assert!(length > 0, "the preamble can't be empty");
+        Self {
+            numbers,
+            idx: 0,
+            preamble: PairSums::new(length),
+        }
+    }
+}
+
+impl<I: Iterator<Item = i64>> Iterator for XmasValidator<I> {
+    type Item = (usize, i64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for value in self.numbers.by_ref() {
+            let idx = self.idx;
+            self.idx += 1;
+            let invalid = idx >= self.preamble.length && !self.preamble.contains(value);
+            self.preamble.push(value);
+            if invalid {
+                return Some((idx, value));
             }
         }
+        None
     }
 }
 
-fn two_sum_exists(data: &[usize], target: usize) -> bool {
-    for i in 0..data.len() {
-        for j in i + 1..data.len() {
-            if data[i] + data[j] == target {
-                return true;
+struct PairSums {
+    length: usize,
+    window: VecDeque<i64>,
+    sums: HashMap<i64, usize>,
+}
+
+impl PairSums {
+    pub fn new(length: usize) -> Self {
+        Self {
+            length,
+            window: VecDeque::with_capacity(length),
+            sums: HashMap::new(),
+        }
+    }
+
+    pub fn contains(&self, sum: i64) -> bool {
+        self.sums.contains_key(&sum)
+    }
+
+    pub fn push(&mut self, value: i64) {
+        if self.window.len() == self.length {
+            let oldest = self.window.pop_front().unwrap();
+            for other in self.window.iter() {
+                let count = self.sums.get_mut(&(oldest + other)).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    self.sums.remove(&(oldest + other));
+                }
             }
         }
+        for other in self.window.iter() {
+            *self.sums.entry(value + other).or_insert(0) += 1;
+        }
+        self.window.push_back(value);
     }
-    false
 }
 
-fn read(filename: &str) -> Vec<usize> {
-    read_to_string(filename)
-        .expect("Failed to read file")
+fn find_encryption_weakness(data: &[i64], target: i64) -> Option<Range<usize>> {
+    ranges_summing_to(data, target).next()
+}
+
+// ranges of at least two numbers summing up to target, ordered by their end; the
+// prefix sums of possible starts are kept in a map so negative values are supported
+fn ranges_summing_to(data: &[i64], target: i64) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut prefix = vec![0; data.len() + 1];
+    for (i, value) in data.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + value;
+    }
+    let mut starts: HashMap<i64, Vec<usize>> = HashMap::new();
+    (2..=data.len()).flat_map(move |end| {
+        starts.entry(prefix[end - 2]).or_default().push(end - 2);
+        starts
+            .get(&(prefix[end] - target))
+            .map(|found| found.iter().map(|&start| start..end).collect::<Vec<_>>())
+            .unwrap_or_default()
+    })
+}
+
+fn min_max(data: &[i64]) -> (i64, i64) {
+    data.iter()
+        .fold((i64::MAX, i64::MIN), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        })
+}
+
+fn read(filename: &str) -> Vec<i64> {
+    let file = File::open(filename).expect("File not found");
+    numbers(BufReader::new(file)).collect()
+}
+
+fn numbers<R: BufRead>(reader: R) -> impl Iterator<Item = i64> {
+    reader
         .lines()
-        .map(|s| s.parse().unwrap())
-        .collect()
+        .map(|line| line.expect("Failed to read line").parse().unwrap())
 }
 
 #[cfg(test)]
@@ -66,12 +137,40 @@ mod tests {
     #[test]
     fn part1_test() {
         let data = read("test-input.txt");
-        assert_eq!(find_first_invalid(&data, 5), 127);
+        assert_eq!(find_first_invalid(&data, 5), Some(127));
+    }
+
+    #[test]
+    fn validator_test() {
+        let input = "1\n2\n3\n7\n5\n12\n9";
+        let invalid = XmasValidator::new(numbers(input.as_bytes()), 3).collect::<Vec<_>>();
+        assert_eq!(invalid, vec![(3, 7), (6, 9)]);
+        assert_eq!(find_first_invalid(&[1, 2, 3, 5, 8], 2), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "the preamble can't be empty")]
+    fn empty_preamble_test() {
+        find_first_invalid(&[1, 2, 3], 0);
     }
 
     #[test]
     fn part2_test() {
         let data = read("test-input.txt");
-        assert_eq!(find_encryption_weakness(&data, 127), 62);
+        let range = find_encryption_weakness(&data, 127).unwrap();
+        assert_eq!(range, 2..6);
+        assert_eq!(min_max(&data[range]), (15, 47));
+    }
+
+    #[test]
+    fn weakness_edge_cases_test() {
+        let data = [5, -3, 8, 10, 2, -2, 10];
+        assert_eq!(find_encryption_weakness(&data, 10), Some(0..3));
+        assert_eq!(
+            ranges_summing_to(&data, 10).collect::<Vec<_>>(),
+            vec![0..3, 3..6, 4..7]
+        );
+        assert_eq!(find_encryption_weakness(&[10, 1, 2], 10), None);
+        assert_eq!(find_encryption_weakness(&[], 0), None);
     }
 }
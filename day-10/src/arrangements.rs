@@ -0,0 +1,167 @@
+use crate::{check_gaps, count_arrangements_from, UnbridgeableGap};
+use num::bigint::BigUint;
+
+// lazily walks all valid chains in lexicographic order. this and the lookups
+// below aren't needed for the answers, they let chains be spot-checked without
+// materializing all of them
+#[allow(dead_code)]
+pub struct Arrangements<'a> {
+    data: &'a [usize],
+    max_gap: usize,
+    chain: Vec<usize>,
+    started: bool,
+}
+
+impl<'a> Arrangements<'a> {
+    #[allow(dead_code)]
+    pub fn new(data: &'a [usize], max_gap: usize) -> Result<Self, UnbridgeableGap> {
+        check_gaps(data, max_gap)?;
+        Ok(Self {
+            data,
+            max_gap,
+            chain: Vec::new(),
+            started: false,
+        })
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.data.len() - 1;
+        if self.started {
+            // advance the deepest adapter that can skip ahead by one, then
+            // complete the chain with every remaining adapter
+            self.chain.pop();
+            loop {
+                let curr = self.chain.pop()?;
+                let prev = *self.chain.last()?;
+                if self.data[curr + 1] - self.data[prev] <= self.max_gap {
+                    self.chain.push(curr + 1);
+                    break;
+                }
+            }
+            self.chain.extend(self.chain.last().unwrap() + 1..=last);
+        } else {
+            self.started = true;
+            self.chain = (0..=last).collect();
+        }
+        Some(self.chain.iter().map(|&i| self.data[i]).collect())
+    }
+}
+
+#[allow(dead_code)]
+pub fn nth_arrangement(
+    data: &[usize],
+    max_gap: usize,
+    k: &BigUint,
+) -> Result<Option<Vec<usize>>, UnbridgeableGap> {
+    let counts = count_arrangements_from(data, max_gap)?;
+    Ok(unrank(data, max_gap, &counts, k.clone()))
+}
+
+#[allow(dead_code)]
+pub fn sample_arrangements(
+    data: &[usize],
+    max_gap: usize,
+    count: usize,
+    seed: u64,
+) -> Result<Vec<Vec<usize>>, UnbridgeableGap> {
+    let counts = count_arrangements_from(data, max_gap)?;
+    let mut rng = SplitMix64::new(seed);
+    Ok((0..count)
+        .map(|_| {
+            let k = rng.below(&counts[0]);
+            unrank(data, max_gap, &counts, k).unwrap()
+        })
+        .collect())
+}
+
+// picks the next adapter by skipping over whole subtrees of chains until the
+// one containing the k-th chain is found
+fn unrank(
+    data: &[usize],
+    max_gap: usize,
+    counts: &[BigUint],
+    mut k: BigUint,
+) -> Option<Vec<usize>> {
+    if k >= counts[0] {
+        return None;
+    }
+    let mut i = 0;
+    let mut chain = vec![data[0]];
+    while i < data.len() - 1 {
+        i = (i + 1..data.len())
+            .take_while(|&next| data[next] - data[i] <= max_gap)
+            .find(|&next| {
+                if k < counts[next] {
+                    return true;
+                }
+                k -= &counts[next];
+                false
+            })
+            .unwrap();
+        chain.push(data[i]);
+    }
+    Some(chain)
+}
+
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        ((z ^ (z >> 31)) >> 32) as u32
+    }
+
+    // uniform value in [0, bound) using rejection sampling over bound's bit length
+    pub fn below(&mut self, bound: &BigUint) -> BigUint {
+        let bits = bound.bits();
+        let digits = bits.div_ceil(32) as usize;
+        loop {
+            let mut value = (0..digits).map(|_| self.next_u32()).collect::<Vec<_>>();
+            if !bits.is_multiple_of(32) {
+                value[digits - 1] &= (1 << (bits % 32)) - 1;
+            }
+            let value = BigUint::new(value);
+            if &value < bound {
+                return value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read;
+
+    #[test]
+    fn arrangements_test() {
+        let data = read("test-input.txt", 3);
+        let chains = Arrangements::new(&data, 3).unwrap().collect::<Vec<_>>();
+        assert_eq!(chains.len(), 8);
+        assert!(chains.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(chains[0], data);
+        assert_eq!(chains[7], vec![0, 1, 4, 7, 10, 12, 15, 16, 19, 22]);
+        for (k, chain) in chains.iter().enumerate() {
+            let k = BigUint::from(k);
+            assert_eq!(nth_arrangement(&data, 3, &k), Ok(Some(chain.clone())));
+        }
+        assert_eq!(nth_arrangement(&data, 3, &BigUint::from(8u32)), Ok(None));
+
+        let samples = sample_arrangements(&data, 3, 20, 42).unwrap();
+        assert_eq!(samples, sample_arrangements(&data, 3, 20, 42).unwrap());
+        assert!(samples.iter().all(|sample| chains.contains(sample)));
+    }
+}
@@ -1,43 +1,75 @@
-use std::collections::HashMap;
+mod arrangements;
+
+use num::bigint::BigUint;
+use num::{One, Zero};
 use std::fs::read_to_string;
 
+const MAX_GAP: usize = 3;
+
 fn main() {
-    let data = read("input.txt");
-    let diffs = find_adapter_distribution(&data);
+    let data = read("input.txt", MAX_GAP);
+    let diffs = find_adapter_distribution(&data, MAX_GAP).expect("Invalid adapter chain");
     println!("part1 solution {}", diffs[1] * diffs[3]);
-    println!("part2 solution {}", find_distinct_arrangements(&data));
+    println!(
+        "part2 solution {}",
+        find_distinct_arrangements(&data, MAX_GAP).expect("Invalid adapter chain")
+    );
 }
 
-fn find_adapter_distribution(data: &[usize]) -> [usize; 4] {
-    data.windows(2).fold([0; 4], |mut acc, values| {
-        acc[values[1] - values[0]] += 1;
-        acc
-    })
+#[derive(Debug, PartialEq)]
+struct UnbridgeableGap {
+    from: usize,
+    to: usize,
 }
 
-fn find_distinct_arrangements(data: &[usize]) -> usize {
-    find_valid_arrangements(data, 0, &mut HashMap::new())
+fn check_gaps(data: &[usize], max_gap: usize) -> Result<(), UnbridgeableGap> {
+    match data
+        .windows(2)
+        .find(|values| values[1] - values[0] > max_gap)
+    {
+        Some(values) => Err(UnbridgeableGap {
+            from: values[0],
+            to: values[1],
+        }),
+        None => Ok(()),
+    }
 }
 
-fn find_valid_arrangements(data: &[usize], i: usize, memo: &mut HashMap<usize, usize>) -> usize {
-    if i == data.len() - 1 {
-        return 1;
-    }
-    if let Some(val) = memo.get(&i) {
-        return *val;
-    }
-    let mut count = 0;
-    for (pos, &val) in data.iter().enumerate().skip(i + 1) {
-        if data[i] + 3 < val {
-            break;
-        }
-        count += find_valid_arrangements(data, pos, memo);
+fn find_adapter_distribution(
+    data: &[usize],
+    max_gap: usize,
+) -> Result<Vec<usize>, UnbridgeableGap> {
+    check_gaps(data, max_gap)?;
+    Ok(data
+        .windows(2)
+        .fold(vec![0; max_gap + 1], |mut acc, values| {
+            acc[values[1] - values[0]] += 1;
+            acc
+        }))
+}
+
+fn find_distinct_arrangements(data: &[usize], max_gap: usize) -> Result<BigUint, UnbridgeableGap> {
+    Ok(count_arrangements_from(data, max_gap)?.swap_remove(0))
+}
+
+// counts[i] holds the number of valid chains from adapter i up to the device
+fn count_arrangements_from(
+    data: &[usize],
+    max_gap: usize,
+) -> Result<Vec<BigUint>, UnbridgeableGap> {
+    check_gaps(data, max_gap)?;
+    let mut counts = vec![BigUint::zero(); data.len()];
+    counts[data.len() - 1] = BigUint::one();
+    for i in (0..data.len() - 1).rev() {
+        counts[i] = (i + 1..data.len())
+            .take_while(|&next| data[next] - data[i] <= max_gap)
+            .map(|next| &counts[next])
+            .sum();
     }
-    memo.insert(i, count);
-    count
+    Ok(counts)
 }
 
-fn read(filename: &str) -> Vec<usize> {
+fn read(filename: &str, max_gap: usize) -> Vec<usize> {
     let content = read_to_string(filename).expect("Failed to read file");
 
     let mut adapters = content
@@ -46,7 +78,7 @@ fn read(filename: &str) -> Vec<usize> {
         .collect::<Vec<_>>();
     adapters.sort_unstable();
     adapters.insert(0, 0);
-    adapters.push(adapters.last().unwrap() + 3);
+    adapters.push(adapters.last().unwrap() + max_gap);
     adapters
 }
 
@@ -56,17 +88,41 @@ mod tests {
 
     #[test]
     fn test1() {
-        let data = read("test-input.txt");
-        let diffs = find_adapter_distribution(&data);
+        let data = read("test-input.txt", 3);
+        let diffs = find_adapter_distribution(&data, 3).unwrap();
         assert_eq!(diffs[1] * diffs[3], 35);
-        assert_eq!(find_distinct_arrangements(&data), 8);
+        assert_eq!(
+            find_distinct_arrangements(&data, 3),
+            Ok(BigUint::from(8u32))
+        );
     }
 
     #[test]
     fn test2() {
-        let data = read("test-input2.txt");
-        let diffs = find_adapter_distribution(&data);
+        let data = read("test-input2.txt", 3);
+        let diffs = find_adapter_distribution(&data, 3).unwrap();
         assert_eq!(diffs[1] * diffs[3], 220);
-        assert_eq!(find_distinct_arrangements(&data), 19208);
+        assert_eq!(
+            find_distinct_arrangements(&data, 3),
+            Ok(BigUint::from(19208u32))
+        );
+    }
+
+    #[test]
+    fn max_gap_test() {
+        let data = read("test-input.txt", 3);
+        assert_eq!(
+            find_distinct_arrangements(&data, 2),
+            Err(UnbridgeableGap { from: 1, to: 4 })
+        );
+        assert_eq!(
+            find_distinct_arrangements(&data, 4),
+            Ok(BigUint::from(170u32))
+        );
+        let data = (0..=200).collect::<Vec<_>>();
+        assert_eq!(
+            find_distinct_arrangements(&data, 3).unwrap().to_string(),
+            "52622583840983769603765180599790256716084480555530641"
+        );
     }
 }
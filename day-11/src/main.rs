@@ -1,23 +1,60 @@
+mod recorder;
+
+use automaton::{DenseAutomaton, Evolve, Grid, Neighborhood, Rules};
+use recorder::{AsciicastRecorder, PpmRecorder, Recorder, TerminalRecorder};
+use std::collections::HashMap;
+use std::env;
 use std::fs::read_to_string;
-use std::thread::sleep;
 use std::time::Duration;
 
-const ESC: &str = "\x1B[";
-const RESET: &str = "\x1B[0m";
+// an empty seat gets occupied with no occupied seats around it, and an occupied one
+// is left once 4 (or 5 when looking further) of the seats it sees are occupied
+const ADJACENT_RULES: &str = "B0/S0123";
+const LOOK_FURTHER_RULES: &str = "B0/S01234";
+
+const USAGE: &str = "usage: day-11 [<part> show <delay-ms> | <part> cast <file> <delay-ms> | <part> ppm <dir> <scale>]";
 
 fn main() {
     let mut data = read("input.txt");
-    println!(
-        "part1 solution: {}",
-        simulate_and_count(&mut data.clone(), false)
-    );
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        println!(
+            "part1 solution: {}",
+            simulate_and_count(&mut data.clone(), false)
+        );
+        println!("part2 solution: {}", simulate_and_count(&mut data, true));
+        return;
+    }
+
+    let look_further = match args[0].as_str() {
+        "1" => false,
+        "2" => true,
+        _ => panic!("{}", USAGE),
+    };
+    let mut recorder = create_recorder(&args[1..]);
+    let simulation = simulate_changes(&mut data, look_further, Some(recorder.as_mut()));
+    println!("{:?}", simulation.outcome);
+    println!("occupied seats per round: {:?}", simulation.history);
+}
 
-    println!("part2 solution: {}", simulate_and_count(&mut data, true));
+fn create_recorder(args: &[String]) -> Box<dyn Recorder> {
+    let delay = |ms: &str| Duration::from_millis(ms.parse().expect("Invalid delay"));
+    match args.iter().map(|s| s.as_str()).collect::<Vec<_>>()[..] {
+        ["show", ms] => Box::new(TerminalRecorder::new(delay(ms))),
+        ["cast", filename, ms] => Box::new(
+            AsciicastRecorder::create(filename, delay(ms)).expect("Failed to create recording"),
+        ),
+        ["ppm", dir, scale] => {
+            let scale = scale.parse().expect("Invalid scale");
+            Box::new(PpmRecorder::new(dir, scale))
+        }
+        _ => panic!("{}", USAGE),
+    }
 }
 
-fn simulate_and_count(data: &mut Vec<Vec<char>>, look_further: bool) -> usize {
-    simulate_changes(data, look_further, false);
-    count_occupied(&data)
+fn simulate_and_count(data: &mut [Vec<char>], look_further: bool) -> usize {
+    simulate_changes(data, look_further, None);
+    count_occupied(data)
 }
 
 fn count_occupied(data: &[Vec<char>]) -> usize {
@@ -26,90 +63,97 @@ fn count_occupied(data: &[Vec<char>]) -> usize {
         .sum()
 }
 
-fn simulate_changes(data: &mut Vec<Vec<char>>, look_further: bool, visualize: bool) {
-    let min_occupied_required = if look_further { 5 } else { 4 };
-    loop {
-        let curr_state = data.clone();
-        if visualize {
-            sleep(Duration::from_millis(750));
-            print_map(&curr_state);
-        }
-        let mut changed = 0;
-        for (x, row) in data.iter_mut().enumerate() {
-            for (y, field) in row.iter_mut().enumerate() {
-                if *field == 'L'
-                    && count_adjacent(&curr_state, x as i32, y as i32, look_further) == 0
-                {
-                    *field = '#';
-                    changed += 1;
-                } else if *field == '#'
-                    && count_adjacent(&curr_state, x as i32, y as i32, look_further)
-                        >= min_occupied_required
-                {
-                    *field = 'L';
-                    changed += 1;
-                }
-            }
-        }
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Stabilized { round: usize },
+    Cycle { start: usize, period: usize },
+}
 
-        if changed == 0 {
-            return;
-        }
-    }
+#[derive(Debug)]
+struct Simulation {
+    outcome: Outcome,
+    // occupied seats in every distinct round, starting with the initial layout
+    history: Vec<usize>,
 }
 
-fn count_adjacent(data: &[Vec<char>], x: i32, y: i32, look_further: bool) -> usize {
-    let mut count = 0;
-    for i in -1..=1 {
-        for j in -1..=1 {
-            let (mut step_x, mut step_y) = (i, j);
-            if i == 0 && j == 0 {
-                continue;
-            }
+fn simulate_changes(
+    data: &mut [Vec<char>],
+    look_further: bool,
+    recorder: Option<&mut dyn Recorder>,
+) -> Simulation {
+    let (neighborhood, rules) = if look_further {
+        (Neighborhood::LineOfSight, LOOK_FURTHER_RULES)
+    } else {
+        (Neighborhood::Adjacent, ADJACENT_RULES)
+    };
+    simulate_with_rules(data, neighborhood, rules.parse().unwrap(), recorder)
+}
 
-            loop {
-                let (next_x, next_y) = (x + step_x, y + step_y);
-                if next_x < 0
-                    || next_x as usize >= data.len()
-                    || next_y < 0
-                    || next_y as usize >= data[x as usize].len()
-                {
-                    break;
-                }
-                if data[(next_x as usize)][(next_y as usize)] == '#' {
-                    count += 1;
-                }
-                if !look_further || data[(next_x as usize)][(next_y as usize)] != '.' {
-                    break;
-                }
-                step_x += i;
-                step_y += j;
-            }
+// runs until a layout repeats, leaving the first repeated layout in data
+fn simulate_with_rules(
+    data: &mut [Vec<char>],
+    neighborhood: Neighborhood,
+    rules: Rules,
+    mut recorder: Option<&mut dyn Recorder>,
+) -> Simulation {
+    let seats = data
+        .iter()
+        .map(|row| row.iter().map(|&field| field != '.').collect())
+        .collect();
+    let occupied = data.iter().enumerate().flat_map(|(x, row)| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, &field)| field == '#')
+            .map(move |(y, _)| (x, y))
+    });
+    let grid = Grid::new(seats, neighborhood);
+    let mut automaton = DenseAutomaton::new(&grid, rules, occupied);
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    loop {
+        let round = history.len();
+        if let Some(&start) = seen.get(&pack(automaton.state())) {
+            apply_state(data, &automaton);
+            let outcome = match round - start {
+                1 => Outcome::Stabilized { round: start },
+                period => Outcome::Cycle { start, period },
+            };
+            return Simulation { outcome, history };
         }
+        seen.insert(pack(automaton.state()), round);
+        history.push(automaton.population());
+        if let Some(recorder) = recorder.as_mut() {
+            apply_state(data, &automaton);
+            recorder.record(data).expect("Failed to record frame");
+        }
+        automaton.step();
     }
-    count
 }
 
-fn print_map(data: &[Vec<char>]) {
-    let black_background = 40;
-    let mut s = format!("{}[2J", 27 as char);
-    for row in data {
-        for field in row {
-            let color = match &field {
-                '#' => 41,
-                '.' => 40,
-                'L' => 47,
-                _ => panic!("unexpected char"),
-            };
-            s.push_str(&format!("{}{2}{1};1m", RESET, color, ESC));
-            s.push(' ');
-        }
+fn pack(state: &[bool]) -> Vec<u64> {
+    state
+        .chunks(64)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |acc, (i, &alive)| acc | (alive as u64) << i)
+        })
+        .collect()
+}
 
-        s.push_str(&format!("{}{2}{1};1m", RESET, black_background, ESC));
-        s.push('\n');
+fn apply_state(data: &mut [Vec<char>], automaton: &DenseAutomaton<(usize, usize)>) {
+    for (x, row) in data.iter_mut().enumerate() {
+        for (y, field) in row.iter_mut().enumerate() {
+            if *field != '.' {
+                *field = if automaton.is_alive(&(x, y)) {
+                    '#'
+                } else {
+                    'L'
+                };
+            }
+        }
     }
-    s.push_str(&format!("{}{2}{1};1m", RESET, black_background, ESC));
-    println!("{}", s);
 }
 
 fn read(filename: &str) -> Vec<Vec<char>> {
@@ -135,4 +179,38 @@ mod tests {
         let mut data = read("test-input.txt");
         assert_eq!(simulate_and_count(&mut data, true), 26);
     }
+
+    #[test]
+    fn outcome_test() {
+        let mut data = read("test-input.txt");
+        let simulation = simulate_changes(&mut data, false, None);
+        assert_eq!(simulation.outcome, Outcome::Stabilized { round: 5 });
+        assert_eq!(simulation.history, vec![0, 71, 20, 51, 30, 37]);
+
+        let mut data = read("test-input.txt");
+        let rules = "B0/S".parse().unwrap();
+        let simulation = simulate_with_rules(&mut data, Neighborhood::Adjacent, rules, None);
+        assert_eq!(
+            simulation.outcome,
+            Outcome::Cycle {
+                start: 0,
+                period: 2
+            }
+        );
+        assert_eq!(simulation.history, vec![0, 71]);
+    }
+
+    #[test]
+    fn asciicast_test() {
+        let mut data = read("test-input.txt");
+        let mut cast = Vec::new();
+        let mut recorder = AsciicastRecorder::new(&mut cast, Duration::from_millis(250));
+        simulate_changes(&mut data, false, Some(&mut recorder));
+        let cast = String::from_utf8(cast).unwrap();
+        let lines = cast.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "{\"version\": 2, \"width\": 10, \"height\": 11}");
+        assert_eq!(lines.len(), 7);
+        assert!(lines[1].starts_with("[0.000000, \"o\", \"\\u001b[2J"));
+        assert!(lines[6].starts_with("[1.250000, \"o\", "));
+    }
 }
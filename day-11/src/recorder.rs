@@ -0,0 +1,171 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+const ESC: &str = "\x1B[";
+const RESET: &str = "\x1B[0m";
+const BLACK_BACKGROUND: u8 = 40;
+
+pub trait Recorder {
+    fn record(&mut self, frame: &[Vec<char>]) -> io::Result<()>;
+}
+
+// ANSI background color of a field, unknown fields being drawn like the floor
+fn field_color(field: char) -> u8 {
+    match field {
+        '#' => 41,
+        'L' => 47,
+        _ => BLACK_BACKGROUND,
+    }
+}
+
+fn field_rgb(field: char) -> [u8; 3] {
+    match field {
+        '#' => [205, 0, 0],
+        'L' => [229, 229, 229],
+        _ => [0, 0, 0],
+    }
+}
+
+fn render_ansi(frame: &[Vec<char>], newline: &str) -> String {
+    let mut s = format!("{}2J{}H", ESC, ESC);
+    for row in frame {
+        for &field in row {
+            s.push_str(&format!("{}{2}{1};1m", RESET, field_color(field), ESC));
+            s.push(' ');
+        }
+        s.push_str(&format!("{}{2}{1};1m", RESET, BLACK_BACKGROUND, ESC));
+        s.push_str(newline);
+    }
+    s.push_str(RESET);
+    s
+}
+
+// draws each frame straight to the terminal, waiting `delay` before every frame
+pub struct TerminalRecorder {
+    delay: Duration,
+}
+
+impl TerminalRecorder {
+    pub fn new(delay: Duration) -> Self {
+        Self { delay }
+    }
+}
+
+impl Recorder for TerminalRecorder {
+    fn record(&mut self, frame: &[Vec<char>]) -> io::Result<()> {
+        sleep(self.delay);
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        write!(out, "{}", render_ansi(frame, "\n"))?;
+        out.flush()
+    }
+}
+
+// writes an asciinema v2 recording, the header being written with the first frame
+pub struct AsciicastRecorder<W: Write> {
+    out: W,
+    delay: Duration,
+    frames: u32,
+}
+
+impl AsciicastRecorder<BufWriter<File>> {
+    pub fn create(filename: &str, delay: Duration) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(filename)?), delay))
+    }
+}
+
+impl<W: Write> AsciicastRecorder<W> {
+    pub fn new(out: W, delay: Duration) -> Self {
+        Self {
+            out,
+            delay,
+            frames: 0,
+        }
+    }
+}
+
+impl<W: Write> Recorder for AsciicastRecorder<W> {
+    fn record(&mut self, frame: &[Vec<char>]) -> io::Result<()> {
+        if self.frames == 0 {
+            let width = frame.iter().map(|row| row.len()).max().unwrap_or(0);
+            writeln!(
+                self.out,
+                "{{\"version\": 2, \"width\": {}, \"height\": {}}}",
+                width.max(1),
+                frame.len() + 1
+            )?;
+        }
+        let time = (self.delay * self.frames).as_secs_f64();
+        writeln!(
+            self.out,
+            "[{:.6}, \"o\", \"{}\"]",
+            time,
+            json_escape(&render_ansi(frame, "\r\n"))
+        )?;
+        self.frames += 1;
+        self.out.flush()
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    s.chars()
+        .fold(String::with_capacity(s.len()), |mut acc, ch| {
+            match ch {
+                '"' => acc.push_str("\\\""),
+                '\\' => acc.push_str("\\\\"),
+                '\n' => acc.push_str("\\n"),
+                '\r' => acc.push_str("\\r"),
+                ch if (ch as u32) < 0x20 => acc.push_str(&format!("\\u{:04x}", ch as u32)),
+                ch => acc.push(ch),
+            }
+            acc
+        })
+}
+
+// writes every frame as a separate binary PPM image, each field being a
+// `scale` x `scale` square
+pub struct PpmRecorder {
+    dir: PathBuf,
+    scale: usize,
+    frames: usize,
+}
+
+impl PpmRecorder {
+    pub fn new(dir: &str, scale: usize) -> Self {
+        Self {
+            dir: PathBuf::from(dir),
+            scale: scale.max(1),
+            frames: 0,
+        }
+    }
+}
+
+impl Recorder for PpmRecorder {
+    fn record(&mut self, frame: &[Vec<char>]) -> io::Result<()> {
+        let path = self.dir.join(format!("frame-{:05}.ppm", self.frames));
+        let mut out = BufWriter::new(File::create(path)?);
+        let width = frame.iter().map(|row| row.len()).max().unwrap_or(0);
+        write!(
+            out,
+            "P6\n{} {}\n255\n",
+            width * self.scale,
+            frame.len() * self.scale
+        )?;
+        for row in frame {
+            let line = (0..width)
+                .flat_map(|y| {
+                    let rgb = field_rgb(*row.get(y).unwrap_or(&'.'));
+                    (0..self.scale).flat_map(move |_| rgb.to_vec())
+                })
+                .collect::<Vec<u8>>();
+            for _ in 0..self.scale {
+                out.write_all(&line)?;
+            }
+        }
+        self.frames += 1;
+        out.flush()
+    }
+}
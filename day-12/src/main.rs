@@ -1,85 +1,225 @@
-use std::fs::read_to_string;
-use std::ops::AddAssign;
+mod navigation;
+mod track;
+
+use navigation::NavigationModel;
+use std::env;
+use std::fmt::{self, Debug, Display};
+use std::fs::{read_to_string, write};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+const USAGE: &str = "usage: day-12 [--continuous] [<model> | <model> replay | <model> stats | <model> svg <file> | <model> geojson <file>], <model> being direct or waypoint";
 
 fn main() {
     let data = read("input.txt");
-    let mut ship = Ship::new(Point::new(1, 0));
-    ship.move_ship(&data, true);
-    println!(
-        "part1 solution: {}",
-        ship.position.x.abs() + ship.position.y.abs()
-    );
-    ship = Ship::new(Point::new(10, -1));
-    ship.move_ship(&data, false);
-    println!(
-        "part2 solution: {}",
-        ship.position.x.abs() + ship.position.y.abs()
-    );
-}
-
-struct Ship {
-    position: Point,
-    waypoint: Point,
-}
-
-impl Ship {
-    pub fn new(waypoint: Point) -> Self {
-        Self {
-            position: Point::new(0, 0),
-            waypoint,
-        }
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let continuous = args.iter().any(|arg| arg == "--continuous");
+    args.retain(|arg| arg != "--continuous");
+    if continuous {
+        run::<f64>(&data, &args);
+    } else {
+        run::<i32>(&data, &args);
     }
+}
 
-    pub fn move_ship(&mut self, instructions: &[Dir], move_ship_pos: bool) {
-        for instruction in instructions.iter() {
-            match &instruction {
-                Dir::East(val) => self.move_in_dir(Point::new(*val, 0), move_ship_pos),
-                Dir::West(val) => self.move_in_dir(Point::new(-*val, 0), move_ship_pos),
-                Dir::North(val) => self.move_in_dir(Point::new(0, -*val), move_ship_pos),
-                Dir::South(val) => self.move_in_dir(Point::new(0, *val), move_ship_pos),
-                Dir::Forward(val) => {
-                    self.position.x += val * self.waypoint.x;
-                    self.position.y += val * self.waypoint.y;
-                }
-                Dir::Right(val) => self.rotate(*val),
-                Dir::Left(val) => self.rotate(-*val),
+fn run<T: Coord>(data: &[Dir], args: &[String]) {
+    if args.is_empty() {
+        let mut ship = Ship::<T>::new(model_by_name("direct"));
+        ship.move_ship(data).expect("Invalid instructions");
+        println!("part1 solution: {:?}", ship.position.manhattan());
+        ship = Ship::new(model_by_name("waypoint"));
+        ship.move_ship(data).expect("Invalid instructions");
+        println!("part2 solution: {:?}", ship.position.manhattan());
+        return;
+    }
+
+    let mut ship = Ship::<T>::new(model_by_name(&args[0]));
+    let states = ship
+        .trajectory(data)
+        .collect::<Result<Vec<_>, _>>()
+        .expect("Invalid instructions");
+    let path = std::iter::once(Point::from_i32(0, 0))
+        .chain(states.iter().map(|state| state.position))
+        .collect::<Vec<_>>();
+    match args[1..].iter().map(|s| s.as_str()).collect::<Vec<_>>()[..] {
+        [] => println!("{:?}", path.last().unwrap().manhattan()),
+        ["replay"] => {
+            for state in states {
+                println!(
+                    "{:<5} position {} waypoint {}",
+                    state.instruction.to_string(),
+                    state.position,
+                    state.waypoint
+                );
             }
         }
+        ["stats"] => println!("{}", track::stats(&path).unwrap()),
+        ["svg", filename] => write(filename, track::to_svg(&path)).expect("Failed to write file"),
+        ["geojson", filename] => {
+            write(filename, track::to_geojson(&path)).expect("Failed to write file")
+        }
+        _ => panic!("{}", USAGE),
     }
+}
 
-    fn move_in_dir(&mut self, dir: Point, move_ship_pos: bool) {
-        if move_ship_pos {
-            self.position += dir;
-        } else {
-            self.waypoint += dir;
+fn model_by_name<T: Coord>(name: &str) -> Box<dyn NavigationModel<T>> {
+    navigation::by_name(name).unwrap_or_else(|| panic!("{}", USAGE))
+}
+
+#[derive(Debug, PartialEq)]
+struct RotationError {
+    degrees: i32,
+}
+
+// i32 coordinates only allow exact quarter turns while f64 ones follow any heading
+trait Coord:
+    Copy
+    + Debug
+    + Display
+    + PartialOrd
+    + From<i32>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    fn abs(self) -> Self;
+
+    fn rotate(point: Point<Self>, degrees: i32) -> Result<Point<Self>, RotationError>;
+}
+
+fn rotate_quarter_turns<T: Coord>(point: Point<T>, degrees: i32) -> Option<Point<T>> {
+    let Point { x, y } = point;
+    match degrees.rem_euclid(360) {
+        0 => Some(Point::new(x, y)),
+        90 => Some(Point::new(-y, x)),
+        180 => Some(Point::new(-x, -y)),
+        270 => Some(Point::new(y, -x)),
+        _ => None,
+    }
+}
+
+impl Coord for i32 {
+    fn abs(self) -> Self {
+        self.abs()
+    }
+
+    fn rotate(point: Point<Self>, degrees: i32) -> Result<Point<Self>, RotationError> {
+        rotate_quarter_turns(point, degrees).ok_or(RotationError { degrees })
+    }
+}
+
+impl Coord for f64 {
+    fn abs(self) -> Self {
+        self.abs()
+    }
+
+    fn rotate(point: Point<Self>, degrees: i32) -> Result<Point<Self>, RotationError> {
+        Ok(rotate_quarter_turns(point, degrees).unwrap_or_else(|| {
+            let (sin, cos) = (degrees as f64).to_radians().sin_cos();
+            let Point { x, y } = point;
+            Point::new(x * cos - y * sin, x * sin + y * cos)
+        }))
+    }
+}
+
+struct Ship<T> {
+    position: Point<T>,
+    waypoint: Point<T>,
+    model: Box<dyn NavigationModel<T>>,
+}
+
+impl<T: Coord> Ship<T> {
+    pub fn new(model: Box<dyn NavigationModel<T>>) -> Self {
+        Self {
+            position: Point::from_i32(0, 0),
+            waypoint: model.initial_waypoint(),
+            model,
         }
     }
 
-    fn rotate(&mut self, val: i32) {
-        let (sin, cos) = (val as f32).to_radians().sin_cos();
-        let Point { x, y } = self.waypoint;
-        self.waypoint.x = (x as f32 * cos - y as f32 * sin).round() as i32;
-        self.waypoint.y = (x as f32 * sin + y as f32 * cos).round() as i32;
+    pub fn move_ship(&mut self, instructions: &[Dir]) -> Result<(), RotationError> {
+        instructions
+            .iter()
+            .try_for_each(|instruction| self.step(instruction))
+    }
+
+    pub fn trajectory<'a>(&'a mut self, instructions: &'a [Dir]) -> Trajectory<'a, T> {
+        Trajectory {
+            ship: self,
+            instructions: instructions.iter(),
+        }
+    }
+
+    fn step(&mut self, instruction: &Dir) -> Result<(), RotationError> {
+        self.model
+            .apply(&mut self.position, &mut self.waypoint, instruction)
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-struct Point {
-    x: i32,
-    y: i32,
+#[derive(Debug, Clone, PartialEq)]
+struct State<'a, T> {
+    instruction: &'a Dir,
+    position: Point<T>,
+    waypoint: Point<T>,
 }
-impl Point {
-    pub fn new(x: i32, y: i32) -> Self {
+
+// yields the ship state after every instruction, leaving the ship where it ended up
+struct Trajectory<'a, T> {
+    ship: &'a mut Ship<T>,
+    instructions: std::slice::Iter<'a, Dir>,
+}
+
+impl<'a, T: Coord> Iterator for Trajectory<'a, T> {
+    type Item = Result<State<'a, T>, RotationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let instruction = self.instructions.next()?;
+        let result = self.ship.step(instruction);
+        Some(result.map(|_| State {
+            instruction,
+            position: self.ship.position,
+            waypoint: self.ship.waypoint,
+        }))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Point<T> {
+    x: T,
+    y: T,
+}
+impl<T: Coord> Point<T> {
+    pub fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+
+    pub fn from_i32(x: i32, y: i32) -> Self {
+        Self::new(T::from(x), T::from(y))
+    }
+
+    pub fn manhattan(&self) -> T {
+        self.x.abs() + self.y.abs()
+    }
 }
-impl AddAssign for Point {
+impl<T: Coord> AddAssign for Point<T> {
     fn add_assign(&mut self, other: Self) {
         *self = Point::new(self.x + other.x, self.y + other.y);
     }
 }
+impl<T: Coord> Sub for Point<T> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+impl<T: Coord> fmt::Display for Point<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
 
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq)]
 enum Dir {
     North(i32),
     South(i32),
@@ -90,6 +230,20 @@ enum Dir {
     Left(i32),
 }
 
+impl fmt::Display for Dir {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Dir::North(val) => write!(f, "N{}", val),
+            Dir::South(val) => write!(f, "S{}", val),
+            Dir::East(val) => write!(f, "E{}", val),
+            Dir::West(val) => write!(f, "W{}", val),
+            Dir::Forward(val) => write!(f, "F{}", val),
+            Dir::Right(val) => write!(f, "R{}", val),
+            Dir::Left(val) => write!(f, "L{}", val),
+        }
+    }
+}
+
 fn read(filename: &str) -> Vec<Dir> {
     read_to_string(filename)
         .expect("Failed to read file")
@@ -118,16 +272,54 @@ mod tests {
     #[test]
     fn test1() {
         let data = read("test-input.txt");
-        let mut ship = Ship::new(Point::new(1, 0));
-        ship.move_ship(&data, true);
-        assert_eq!(ship.position.x.abs() + ship.position.y.abs(), 25);
+        let mut ship = Ship::<i32>::new(Box::new(navigation::DirectHeading));
+        ship.move_ship(&data).unwrap();
+        assert_eq!(ship.position.manhattan(), 25);
     }
 
     #[test]
     fn test2() {
         let data = read("test-input.txt");
-        let mut ship = Ship::new(Point::new(10, -1));
-        ship.move_ship(&data, false);
-        assert_eq!(ship.position.x.abs() + ship.position.y.abs(), 286);
+        let mut ship = Ship::<i32>::new(Box::new(navigation::Waypoint));
+        ship.move_ship(&data).unwrap();
+        assert_eq!(ship.position.manhattan(), 286);
+    }
+
+    #[test]
+    fn trajectory_test() {
+        let data = read("test-input.txt");
+        let mut ship = Ship::<i32>::new(model_by_name("waypoint"));
+        let states = ship
+            .trajectory(&data)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            states[3],
+            State {
+                instruction: &Dir::Right(90),
+                position: Point::new(170, -38),
+                waypoint: Point::new(4, 10),
+            }
+        );
+        let last = states.last().unwrap().position;
+        assert_eq!(last, Point::new(214, 72));
+        assert_eq!(last, ship.position);
+    }
+
+    #[test]
+    fn rotation_test() {
+        let data = vec![Dir::Right(45), Dir::Forward(2)];
+        let mut ship = Ship::<i32>::new(model_by_name("direct"));
+        assert_eq!(ship.move_ship(&data), Err(RotationError { degrees: 45 }));
+
+        let mut ship = Ship::<f64>::new(model_by_name("direct"));
+        ship.move_ship(&data).unwrap();
+        assert!((ship.position.x - 2f64.sqrt()).abs() < 1e-9);
+        assert!((ship.position.y - 2f64.sqrt()).abs() < 1e-9);
+
+        let data = read("test-input.txt");
+        let mut ship = Ship::<f64>::new(model_by_name("waypoint"));
+        ship.move_ship(&data).unwrap();
+        assert_eq!(ship.position.manhattan(), 286.0);
     }
 }
@@ -0,0 +1,93 @@
+use crate::{Coord, Dir, Point, RotationError};
+
+// decides what every instruction does to the ship position and its waypoint
+pub trait NavigationModel<T: Coord> {
+    fn initial_waypoint(&self) -> Point<T>;
+
+    fn apply(
+        &self,
+        position: &mut Point<T>,
+        waypoint: &mut Point<T>,
+        instruction: &Dir,
+    ) -> Result<(), RotationError>;
+}
+
+pub fn by_name<T: Coord>(name: &str) -> Option<Box<dyn NavigationModel<T>>> {
+    match name {
+        "direct" => Some(Box::new(DirectHeading)),
+        "waypoint" => Some(Box::new(Waypoint)),
+        _ => None,
+    }
+}
+
+// cardinal directions move the ship itself, the waypoint being just its heading
+pub struct DirectHeading;
+
+impl<T: Coord> NavigationModel<T> for DirectHeading {
+    fn initial_waypoint(&self) -> Point<T> {
+        Point::from_i32(1, 0)
+    }
+
+    fn apply(
+        &self,
+        position: &mut Point<T>,
+        waypoint: &mut Point<T>,
+        instruction: &Dir,
+    ) -> Result<(), RotationError> {
+        match translation(instruction) {
+            Some(dir) => *position += dir,
+            None => steer(position, waypoint, instruction)?,
+        }
+        Ok(())
+    }
+}
+
+// cardinal directions move the waypoint relative to the ship
+pub struct Waypoint;
+
+impl<T: Coord> NavigationModel<T> for Waypoint {
+    fn initial_waypoint(&self) -> Point<T> {
+        Point::from_i32(10, -1)
+    }
+
+    fn apply(
+        &self,
+        position: &mut Point<T>,
+        waypoint: &mut Point<T>,
+        instruction: &Dir,
+    ) -> Result<(), RotationError> {
+        match translation(instruction) {
+            Some(dir) => *waypoint += dir,
+            None => steer(position, waypoint, instruction)?,
+        }
+        Ok(())
+    }
+}
+
+fn translation<T: Coord>(instruction: &Dir) -> Option<Point<T>> {
+    match *instruction {
+        Dir::East(val) => Some(Point::from_i32(val, 0)),
+        Dir::West(val) => Some(Point::from_i32(-val, 0)),
+        Dir::North(val) => Some(Point::from_i32(0, -val)),
+        Dir::South(val) => Some(Point::from_i32(0, val)),
+        _ => None,
+    }
+}
+
+// moves towards the waypoint or turns it around the ship
+fn steer<T: Coord>(
+    position: &mut Point<T>,
+    waypoint: &mut Point<T>,
+    instruction: &Dir,
+) -> Result<(), RotationError> {
+    match *instruction {
+        Dir::Forward(val) => {
+            let val = T::from(val);
+            *position += Point::new(val * waypoint.x, val * waypoint.y);
+        }
+        Dir::Right(val) => *waypoint = T::rotate(*waypoint, val)?,
+        Dir::Left(val) => *waypoint = T::rotate(*waypoint, -val)?,
+        _ => unreachable!(),
+    }
+    Ok(())
+}
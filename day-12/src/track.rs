@@ -0,0 +1,102 @@
+use crate::{Coord, Point};
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub struct Stats<T> {
+    pub min: Point<T>,
+    pub max: Point<T>,
+    // the largest manhattan distance from the start reached along the way
+    pub max_distance: T,
+}
+
+impl<T: Coord> fmt::Display for Stats<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "bounding box {} to {}, max distance {}",
+            self.min, self.max, self.max_distance
+        )
+    }
+}
+
+pub fn stats<T: Coord>(path: &[Point<T>]) -> Option<Stats<T>> {
+    let start = *path.first()?;
+    let max_of = |a: T, b: T| if b > a { b } else { a };
+    let min_of = |a: T, b: T| if b < a { b } else { a };
+    Some(path.iter().fold(
+        Stats {
+            min: start,
+            max: start,
+            max_distance: T::from(0),
+        },
+        |acc, &p| Stats {
+            min: Point::new(min_of(acc.min.x, p.x), min_of(acc.min.y, p.y)),
+            max: Point::new(max_of(acc.max.x, p.x), max_of(acc.max.y, p.y)),
+            max_distance: max_of(acc.max_distance, (p - start).manhattan()),
+        },
+    ))
+}
+
+// svg has y pointing down just like south does, so points are written as they are.
+// the box gets a margin of one unit and never collapses on a straight path
+pub fn to_svg<T: Coord>(path: &[Point<T>]) -> String {
+    let (min, max) = stats(path).map_or((Point::from_i32(0, 0), Point::from_i32(0, 0)), |s| {
+        (s.min, s.max)
+    });
+    let (one, two) = (T::from(1), T::from(2));
+    let padded = |size: T| if size < one { one + two } else { size + two };
+    let size = max - min;
+    let (min, size) = (
+        Point::new(min.x - one, min.y - one),
+        Point::new(padded(size.x), padded(size.y)),
+    );
+    let points = path
+        .iter()
+        .map(|p| format!("{},{}", p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">\n  \
+         <polyline points=\"{}\" fill=\"none\" stroke=\"black\" vector-effect=\"non-scaling-stroke\"/>\n\
+         </svg>\n",
+        min.x, min.y, size.x, size.y, points
+    )
+}
+
+// geojson has y pointing north, so the y axis gets flipped
+pub fn to_geojson<T: Coord>(path: &[Point<T>]) -> String {
+    let coordinates = path
+        .iter()
+        .map(|p| format!("[{}, {}]", p.x, -p.y))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "{{\"type\": \"Feature\", \"properties\": {{}}, \"geometry\": {{\"type\": \"LineString\", \"coordinates\": [{}]}}}}\n",
+        coordinates
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_test() {
+        let path = vec![Point::new(0, 0), Point::new(10, 0), Point::new(10, -3)];
+        assert_eq!(
+            stats(&path),
+            Some(Stats {
+                min: Point::new(0, -3),
+                max: Point::new(10, 0),
+                max_distance: 13
+            })
+        );
+        assert!(to_svg(&path).contains("viewBox=\"-1 -4 12 5\""));
+        assert!(to_svg(&path).contains("points=\"0,0 10,0 10,-3\""));
+        assert!(to_geojson(&path).contains("\"coordinates\": [[0, 0], [10, 0], [10, 3]]"));
+
+        let straight = vec![Point::new(0, 0), Point::new(0, 5)];
+        assert!(to_svg(&straight).contains("viewBox=\"-1 -1 3 7\""));
+        assert!(to_svg(&[Point::new(2.5, 1.0)]).contains("viewBox=\"1.5 0 3 3\""));
+    }
+}
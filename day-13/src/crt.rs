@@ -0,0 +1,122 @@
+use num::bigint::BigInt;
+use num::{CheckedAdd, CheckedMul, Integer, Signed};
+
+#[derive(Debug)]
+struct Overflow;
+
+// finds x with x ≡ residue (mod modulus) for every pair, returned as the smallest
+// non-negative x together with the modulus it repeats with. moduli don't need to
+// be coprime, None is returned when the congruences contradict each other or a
+// modulus isn't positive
+pub fn solve(congruences: &[(i128, i128)]) -> Option<(BigInt, BigInt)> {
+    let mut acc = (0, 1);
+    for (i, congruence) in congruences.iter().enumerate() {
+        match merge(&acc, &normalize(congruence)?) {
+            Ok(Some(next)) => acc = next,
+            Ok(None) => return None,
+            // continue with big integers from the first congruence that didn't fit
+            Err(Overflow) => return solve_big((acc.0.into(), acc.1.into()), &congruences[i..]),
+        }
+    }
+    Some((acc.0.into(), acc.1.into()))
+}
+
+fn solve_big(mut acc: (BigInt, BigInt), congruences: &[(i128, i128)]) -> Option<(BigInt, BigInt)> {
+    for &(residue, modulus) in congruences {
+        let congruence = normalize(&(BigInt::from(residue), BigInt::from(modulus)))?;
+        acc = merge(&acc, &congruence).expect("big integers can't overflow")?;
+    }
+    Some(acc)
+}
+
+fn normalize<T: Integer + Signed + Clone>((residue, modulus): &(T, T)) -> Option<(T, T)> {
+    if !modulus.is_positive() {
+        return None;
+    }
+    Some((residue.mod_floor(modulus), modulus.clone()))
+}
+
+// returns (g, x, y) with a * x + b * y = g = gcd(a, b)
+fn extended_gcd<T: Integer + Signed + Clone>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::one(), T::zero());
+    let (mut old_y, mut y) = (T::zero(), T::one());
+    while !r.is_zero() {
+        let q = old_r.div_floor(&r);
+        let next_r = old_r - q.clone() * r.clone();
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_x = old_x - q.clone() * x.clone();
+        old_x = std::mem::replace(&mut x, next_x);
+        let next_y = old_y - q * y.clone();
+        old_y = std::mem::replace(&mut y, next_y);
+    }
+    (old_r, old_x, old_y)
+}
+
+// combines x ≡ r1 (mod m1) and x ≡ r2 (mod m2) into x ≡ r (mod lcm(m1, m2)),
+// both residues being already reduced
+fn merge<T>(a: &(T, T), b: &(T, T)) -> Result<Option<(T, T)>, Overflow>
+where
+    T: Integer + Signed + Clone + CheckedMul + CheckedAdd,
+{
+    let ((r1, m1), (r2, m2)) = (a, b);
+    let (g, p, _) = extended_gcd(m1.clone(), m2.clone());
+    let diff = r2.clone() - r1.clone();
+    if !diff.is_multiple_of(&g) {
+        return Ok(None);
+    }
+    let step = m2.clone() / g.clone();
+    let k = (diff / g.clone())
+        .checked_mul(&p)
+        .ok_or(Overflow)?
+        .mod_floor(&step);
+    let lcm = (m1.clone() / g).checked_mul(m2).ok_or(Overflow)?;
+    let x = m1
+        .checked_mul(&k)
+        .and_then(|offset| r1.checked_add(&offset))
+        .ok_or(Overflow)?;
+    Ok(Some((x.mod_floor(&lcm), lcm)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_coprime_test() {
+        assert_eq!(
+            solve(&[(2, 6), (8, 10), (-1, 7)]),
+            Some((BigInt::from(188), BigInt::from(210)))
+        );
+        assert_eq!(solve(&[(1, 4), (2, 6)]), None);
+        assert_eq!(solve(&[]), Some((BigInt::from(0), BigInt::from(1))));
+    }
+
+    #[test]
+    fn non_positive_modulus_test() {
+        assert_eq!(solve(&[(1, 4), (0, 0)]), None);
+        assert_eq!(solve(&[(2, -3)]), None);
+        // the modulus is checked after falling back to big integers too
+        assert_eq!(solve(&[(5, 2147483647), (7, i128::MAX), (1, -5)]), None);
+    }
+
+    #[test]
+    fn overflow_test() {
+        let congruences = [
+            (5, 2147483647),
+            (7, 2305843009213693951),
+            (11, 618970019642690137449562111),
+        ];
+        let (x, modulus) = solve(&congruences).unwrap();
+        assert_eq!(
+            modulus,
+            congruences
+                .iter()
+                .map(|&(_, m)| BigInt::from(m))
+                .product::<BigInt>()
+        );
+        for &(residue, m) in congruences.iter() {
+            assert_eq!(&x % BigInt::from(m), BigInt::from(residue));
+        }
+    }
+}
@@ -1,64 +1,99 @@
+mod crt;
+
 use num::bigint::BigInt;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fs::read_to_string;
 
 fn main() {
-    let (id, buses) = read("input.txt");
+    let schedule = read("input.txt");
+    println!("part1 solution {}", find_first_to_depart(&schedule));
+    println!(
+        "part2 solution {}",
+        find_first_depart_to_all(&schedule.buses).expect("No timestamp fits all buses")
+    );
+}
 
-    println!("part1 solution {}", find_first_to_depart(id, &buses));
-    println!("part2 solution {}", find_first_depart_to_all(&buses));
+#[derive(Debug, PartialEq)]
+struct Schedule {
+    earliest: u64,
+    // None for the slots marked with x
+    buses: Vec<Option<u64>>,
 }
 
-fn find_first_to_depart(id: usize, buses: &[usize]) -> usize {
-    let best_bus = buses
-        .iter()
-        .filter(|&&bus_id| bus_id > 0)
-        .map(|bus_id| {
-            let remaining = id.rem_euclid(*bus_id);
-            (*bus_id, *bus_id - remaining)
-        })
-        .min_by_key(|a| a.1)
-        .unwrap();
-    best_bus.0 * best_bus.1
+impl Schedule {
+    // ids of the buses in service along with their offset in the schedule
+    pub fn in_service(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.buses
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, bus)| bus.map(|id| (offset, id)))
+    }
+
+    // every (time, bus) departure at or after `timestamp` in chronological order
+    pub fn departures(&self, timestamp: u64) -> Departures {
+        let queue = self
+            .in_service()
+            .map(|(_, id)| Reverse((timestamp.div_ceil(id) * id, id)))
+            .collect();
+        Departures { queue }
+    }
 }
 
-// using the Chinese remainder theorem
-fn find_first_depart_to_all(buses: &[usize]) -> BigInt {
-    let values = buses
-        .iter()
-        .enumerate()
-        .filter(|(_i, &id)| id > 0)
-        .map(|v| (BigInt::from(v.0), BigInt::from(*v.1)))
-        .collect::<Vec<(BigInt, BigInt)>>();
+struct Departures {
+    queue: BinaryHeap<Reverse<(u64, u64)>>,
+}
 
-    let mod_multi = values.iter().map(|(_, val)| val).product::<BigInt>();
+impl Iterator for Departures {
+    type Item = (u64, u64);
 
-    let m_values = values
-        .iter()
-        .map(|(_, val)| &mod_multi / val)
-        .collect::<Vec<BigInt>>();
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((time, id)) = self.queue.pop()?;
+        self.queue.push(Reverse((time + id, id)));
+        Some((time, id))
+    }
+}
 
-    let sum: BigInt = values
+fn find_first_to_depart(schedule: &Schedule) -> u64 {
+    let (time, bus) = schedule
+        .departures(schedule.earliest)
+        .next()
+        .expect("No buses in service");
+    bus * (time - schedule.earliest)
+}
+
+fn find_first_depart_to_all(buses: &[Option<u64>]) -> Option<BigInt> {
+    find_nth_depart_to_all(buses, 0)
+}
+
+// using the Chinese remainder theorem, bus i has to depart at t + i. solutions
+// repeat with the combined period, so the k-th one (counting from 0) is k periods later
+fn find_nth_depart_to_all(buses: &[Option<u64>], k: u64) -> Option<BigInt> {
+    let congruences = buses
         .iter()
-        .zip(m_values.iter())
-        .map(|((delay, time), m_val)| {
-            let y = (0..)
-                .find(|&curr| (m_val * BigInt::from(curr)) % time == BigInt::from(1))
-                .unwrap();
-            let rem = (time - delay) % time;
-            rem * m_val * y
-        })
-        .sum();
-    sum % mod_multi
+        .enumerate()
+        .filter_map(|(i, bus)| bus.map(|id| (-(i as i128), id as i128)))
+        .collect::<Vec<_>>();
+    crt::solve(&congruences).map(|(timestamp, period)| timestamp + period * k)
 }
 
-fn read(filename: &str) -> (usize, Vec<usize>) {
+fn read(filename: &str) -> Schedule {
     let content = read_to_string(filename).expect("Failed to read file");
     let lines = content.split('\n').collect::<Vec<&str>>();
-    let ids = lines[1]
+    let buses = lines[1]
         .split_terminator(',')
-        .map(|id| id.parse().unwrap_or(0))
-        .collect::<Vec<usize>>();
-    (lines[0].parse().unwrap(), ids)
+        .map(|id| match id {
+            "x" => None,
+            _ => match id.parse().expect("Invalid bus id") {
+                0 => panic!("Bus ids have to be positive"),
+                id => Some(id),
+            },
+        })
+        .collect();
+    Schedule {
+        earliest: lines[0].parse().unwrap(),
+        buses,
+    }
 }
 
 #[cfg(test)]
@@ -67,43 +102,75 @@ mod tests {
 
     #[test]
     fn test1() {
-        let (id, buses) = read("test-input.txt");
-        assert_eq!(find_first_to_depart(id, &buses), 295);
+        let schedule = read("test-input.txt");
+        assert_eq!(find_first_to_depart(&schedule), 295);
     }
 
     #[test]
     fn test2() {
-        let (_, buses) = read("test-input.txt");
-        assert_eq!(find_first_depart_to_all(&buses), BigInt::from(1068781));
+        let buses = read("test-input.txt").buses;
+        assert_eq!(
+            find_first_depart_to_all(&buses),
+            Some(BigInt::from(1068781))
+        );
     }
 
     #[test]
     fn test3() {
-        let (_, buses) = read("test-input2.txt");
-        assert_eq!(find_first_depart_to_all(&buses), BigInt::from(3417));
+        let buses = read("test-input2.txt").buses;
+        assert_eq!(find_first_depart_to_all(&buses), Some(BigInt::from(3417)));
     }
 
     #[test]
     fn test4() {
-        let (_, buses) = read("test-input3.txt");
-        assert_eq!(find_first_depart_to_all(&buses), BigInt::from(754018));
+        let buses = read("test-input3.txt").buses;
+        assert_eq!(find_first_depart_to_all(&buses), Some(BigInt::from(754018)));
     }
 
     #[test]
     fn test5() {
-        let (_, buses) = read("test-input4.txt");
-        assert_eq!(find_first_depart_to_all(&buses), BigInt::from(779210));
+        let buses = read("test-input4.txt").buses;
+        assert_eq!(find_first_depart_to_all(&buses), Some(BigInt::from(779210)));
     }
 
     #[test]
     fn test6() {
-        let (_, buses) = read("test-input5.txt");
-        assert_eq!(find_first_depart_to_all(&buses), BigInt::from(1261476));
+        let buses = read("test-input5.txt").buses;
+        assert_eq!(
+            find_first_depart_to_all(&buses),
+            Some(BigInt::from(1261476))
+        );
     }
 
     #[test]
     fn test7() {
-        let (_, buses) = read("test-input6.txt");
-        assert_eq!(find_first_depart_to_all(&buses), BigInt::from(1202161486));
+        let buses = read("test-input6.txt").buses;
+        assert_eq!(
+            find_first_depart_to_all(&buses),
+            Some(BigInt::from(1202161486))
+        );
+    }
+
+    #[test]
+    fn queries_test() {
+        let schedule = read("test-input.txt");
+        assert_eq!(schedule.buses[..3], [Some(7), Some(13), None]);
+        assert_eq!(
+            schedule.departures(939).take(5).collect::<Vec<_>>(),
+            vec![(944, 59), (945, 7), (949, 13), (950, 19), (952, 7)]
+        );
+        assert_eq!(schedule.departures(945).next(), Some((945, 7)));
+
+        let buses = read("test-input2.txt").buses;
+        assert_eq!(
+            find_nth_depart_to_all(&buses, 2),
+            Some(BigInt::from(3417 + 2 * 4199))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Bus ids have to be positive")]
+    fn zero_id_test() {
+        read("test-input7.txt");
     }
 }
@@ -0,0 +1,185 @@
+use crate::memory::{AddressPattern, SparseMemory};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mask {
+    // bits that aren't forced to 0
+    pub and: u64,
+    // bits forced to 1
+    pub or: u64,
+    // bits marked with X
+    pub floating: u64,
+}
+
+impl Mask {
+    pub fn parse(s: &str, width: u32) -> Result<Self, String> {
+        if s.len() != width as usize {
+            return Err(format!("expected {} mask bits, got {}", width, s.len()));
+        }
+        s.chars().try_fold(
+            Mask {
+                and: 0,
+                or: 0,
+                floating: 0,
+            },
+            |mask, ch| {
+                let (and, or, floating) = (mask.and << 1, mask.or << 1, mask.floating << 1);
+                match ch {
+                    '0' => Ok(Mask { and, or, floating }),
+                    '1' => Ok(Mask {
+                        and: and | 1,
+                        or: or | 1,
+                        floating,
+                    }),
+                    'X' => Ok(Mask {
+                        and: and | 1,
+                        or,
+                        floating: floating | 1,
+                    }),
+                    _ => Err(format!("unexpected mask bit {}", ch)),
+                }
+            },
+        )
+    }
+
+    pub fn apply(&self, value: u64) -> u64 {
+        value & self.and | self.or
+    }
+}
+
+fn word_mask(width: u32) -> u64 {
+    assert!(
+        (1..=64).contains(&width),
+        "unsupported word width {}",
+        width
+    );
+    u64::MAX >> (64 - width)
+}
+
+pub trait DecoderChip {
+    fn width(&self) -> u32;
+
+    fn write(&mut self, mask: &Mask, address: u64, value: u64);
+
+    fn sum(&self) -> u128;
+
+    // every written address pattern with its value, ordered by address. the
+    // patterns are disjoint, but v2 doesn't expand them into single addresses
+    fn dump(&self) -> Vec<(AddressPattern, u64)>;
+}
+
+pub fn by_version(version: &str, width: u32) -> Option<Box<dyn DecoderChip>> {
+    match version {
+        "v1" => Some(Box::new(DecoderV1::new(width))),
+        "v2" => Some(Box::new(DecoderV2::new(width))),
+        _ => None,
+    }
+}
+
+// the mask gets applied to the written values
+pub struct DecoderV1 {
+    word: u64,
+    memory: HashMap<u64, u64>,
+}
+
+impl DecoderV1 {
+    pub fn new(width: u32) -> Self {
+        Self {
+            word: word_mask(width),
+            memory: HashMap::new(),
+        }
+    }
+}
+
+impl DecoderChip for DecoderV1 {
+    fn width(&self) -> u32 {
+        self.word.count_ones()
+    }
+
+    fn write(&mut self, mask: &Mask, address: u64, value: u64) {
+        self.memory
+            .insert(address & self.word, mask.apply(value) & self.word);
+    }
+
+    fn sum(&self) -> u128 {
+        self.memory.values().map(|&val| val as u128).sum()
+    }
+
+    fn dump(&self) -> Vec<(AddressPattern, u64)> {
+        let mut dump = self
+            .memory
+            .iter()
+            .map(|(&addr, &val)| (AddressPattern::new(addr, 0), val))
+            .collect::<Vec<_>>();
+        dump.sort_unstable();
+        dump
+    }
+}
+
+// the mask gets applied to the addresses, floating bits writing to all of them
+pub struct DecoderV2 {
+    word: u64,
+    memory: SparseMemory,
+}
+
+impl DecoderV2 {
+    pub fn new(width: u32) -> Self {
+        Self {
+            word: word_mask(width),
+            memory: SparseMemory::new(),
+        }
+    }
+}
+
+impl DecoderChip for DecoderV2 {
+    fn width(&self) -> u32 {
+        self.word.count_ones()
+    }
+
+    fn write(&mut self, mask: &Mask, address: u64, value: u64) {
+        let pattern = AddressPattern::new((address | mask.or) & self.word, mask.floating);
+        self.memory.write(pattern, value & self.word);
+    }
+
+    fn sum(&self) -> u128 {
+        self.memory.sum()
+    }
+
+    // one entry per disjoint pattern, floating bits left in place since a
+    // single write can cover up to 2^width addresses
+    fn dump(&self) -> Vec<(AddressPattern, u64)> {
+        self.memory.dump()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn narrow_word_test() {
+        let mask = Mask::parse("1X0X", 4).unwrap();
+        assert_eq!(
+            mask,
+            Mask {
+                and: 0b1101,
+                or: 0b1000,
+                floating: 0b0101
+            }
+        );
+        assert!(Mask::parse("1X0X", 5).is_err());
+        assert!(Mask::parse("1X0Y", 4).is_err());
+
+        let mut chip = DecoderV2::new(4);
+        chip.write(&mask, 0b0010, 0b11111);
+        chip.write(&Mask::parse("0000", 4).unwrap(), 0b11010, 1);
+        assert_eq!(chip.sum(), 3 * 15 + 1);
+        assert_eq!(
+            chip.dump()
+                .iter()
+                .map(|(pattern, val)| format!("{} = {}", pattern.format(4), val))
+                .collect::<Vec<_>>(),
+            vec!["10 = 1", "1X11 = 15", "14 = 15"]
+        );
+    }
+}
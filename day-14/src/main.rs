@@ -1,87 +1,67 @@
-use std::collections::HashMap;
+mod chip;
+mod memory;
+
+use chip::{DecoderChip, Mask};
+use std::env;
 use std::fs::read_to_string;
 
-fn main() {
-    let data = read("input.txt");
-    let mut memory = HashMap::new();
-    println!("part1 solution: {}", run_and_sum_values(&mut memory, &data));
+const WORD_WIDTH: u32 = 36;
 
-    memory.clear();
-    println!(
-        "part2 solution: {}",
-        run_and_sum_values2(&mut memory, &data)
-    );
-}
+const USAGE: &str = "usage: day-14 [--width <bits>] [<v1|v2> dump]
+v2 dumps disjoint address patterns rather than single addresses, X marking the floating bits";
 
-fn run_and_sum_values(memory: &mut HashMap<usize, usize>, data: &[Data]) -> usize {
-    for item in data {
-        let Data { mask, instructions } = item;
-        for &(addr, new_val) in instructions {
-            let mut val = new_val;
-            for (i, ch) in mask.iter().rev().enumerate() {
-                match ch {
-                    '1' => val |= 1 << i,
-                    '0' => val &= !(1 << i),
-                    _ => {}
-                }
-            }
-            memory.insert(addr, val);
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let width = match args.iter().position(|arg| arg == "--width") {
+        Some(i) => {
+            let width = args.get(i + 1).and_then(|w| w.parse().ok()).expect(USAGE);
+            args.drain(i..i + 2);
+            width
         }
-    }
-    memory.values().sum()
-}
-
-fn run_and_sum_values2(memory: &mut HashMap<usize, usize>, data: &[Data]) -> usize {
-    for item in data {
-        for &(addr, val) in &item.instructions {
-            let (addr_mask, initial_val) = generate_addr_mask(addr, &item.mask);
-            let mut addresses = Vec::new();
-            generate_address_values(&addr_mask, initial_val, 0, &mut addresses);
-            for actual_addr in addresses {
-                memory.insert(actual_addr, val);
-            }
+        None => WORD_WIDTH,
+    };
+    let data = read("input.txt", width);
+    match args.iter().map(|s| s.as_str()).collect::<Vec<_>>()[..] {
+        [] => {
+            println!(
+                "part1 solution: {}",
+                run(chip_by_version("v1", width).as_mut(), &data)
+            );
+            println!(
+                "part2 solution: {}",
+                run(chip_by_version("v2", width).as_mut(), &data)
+            );
         }
-    }
-    memory.values().sum()
-}
-
-fn generate_addr_mask(address: usize, mask: &[char]) -> ([char; 36], usize) {
-    let mut res = ['0'; 36];
-    let mut val = 0;
-    for ((i, mask_ch), ch) in mask.iter().rev().enumerate().zip(res.iter_mut().rev()) {
-        match mask_ch {
-            '1' => {
-                *ch = '1';
-                val |= 1 << i;
-            }
-            'X' => {
-                *ch = 'X';
-                val &= !(1 << i);
+        [version, "dump"] => {
+            let mut chip = chip_by_version(version, width);
+            run(chip.as_mut(), &data);
+            for (pattern, val) in chip.dump() {
+                println!("mem[{}] = {}", pattern.format(chip.width()), val);
             }
-            _ => val |= address & (1 << i),
         }
+        _ => panic!("{}", USAGE),
     }
-    (res, val)
 }
 
-fn generate_address_values(mask: &[char; 36], candidate: usize, i: usize, acc: &mut Vec<usize>) {
-    if i == mask.len() {
-        acc.push(candidate);
-        return;
-    }
+fn chip_by_version(version: &str, width: u32) -> Box<dyn DecoderChip> {
+    chip::by_version(version, width).unwrap_or_else(|| panic!("{}", USAGE))
+}
 
-    generate_address_values(mask, candidate, i + 1, acc);
-    if mask[i] == 'X' {
-        generate_address_values(mask, candidate | 1 << (mask.len() - 1 - i), i + 1, acc);
+fn run(chip: &mut dyn DecoderChip, data: &[Data]) -> u128 {
+    for item in data {
+        for &(addr, val) in &item.instructions {
+            chip.write(&item.mask, addr, val);
+        }
     }
+    chip.sum()
 }
 
 struct Data {
-    mask: Vec<char>,
-    instructions: Vec<(usize, usize)>,
+    mask: Mask,
+    instructions: Vec<(u64, u64)>,
 }
 
-fn read(filename: &str) -> Vec<Data> {
+fn read(filename: &str, width: u32) -> Vec<Data> {
     read_to_string(filename)
         .expect("Failed to read file")
         .split_terminator("mask = ")
@@ -99,7 +79,7 @@ fn read(filename: &str) -> Vec<Data> {
                 })
                 .collect();
             Data {
-                mask: lines[0].chars().collect(),
+                mask: Mask::parse(lines[0], width).expect("Invalid mask"),
                 instructions,
             }
         })
@@ -112,15 +92,15 @@ mod tests {
 
     #[test]
     fn test1() {
-        let data = read("test-input.txt");
-        let mut memory = HashMap::new();
-        assert_eq!(run_and_sum_values(&mut memory, &data), 165);
+        let data = read("test-input.txt", WORD_WIDTH);
+        let mut chip = chip::DecoderV1::new(WORD_WIDTH);
+        assert_eq!(run(&mut chip, &data), 165);
     }
 
     #[test]
     fn test2() {
-        let data = read("test-input2.txt");
-        let mut memory = HashMap::new();
-        assert_eq!(run_and_sum_values2(&mut memory, &data), 208);
+        let data = read("test-input2.txt", WORD_WIDTH);
+        let mut chip = chip::DecoderV2::new(WORD_WIDTH);
+        assert_eq!(run(&mut chip, &data), 208);
     }
 }
@@ -0,0 +1,117 @@
+// a set of addresses, the fixed bits of `value` combined with every setting of
+// the `floating` bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AddressPattern {
+    value: u64,
+    floating: u64,
+}
+
+impl AddressPattern {
+    pub fn new(value: u64, floating: u64) -> Self {
+        Self {
+            value: value & !floating,
+            floating,
+        }
+    }
+
+    pub fn size(&self) -> u128 {
+        1 << self.floating.count_ones()
+    }
+
+    // plain addresses are written in decimal, patterns as `width` bits with X
+    // marking the floating ones
+    pub fn format(&self, width: u32) -> String {
+        if self.floating == 0 {
+            return self.value.to_string();
+        }
+        (0..width)
+            .rev()
+            .map(|i| match (self.floating >> i & 1, self.value >> i & 1) {
+                (1, _) => 'X',
+                (_, 1) => '1',
+                _ => '0',
+            })
+            .collect()
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        (self.value ^ other.value) & !self.floating & !other.floating == 0
+    }
+
+    // splits the addresses not covered by `other` into disjoint patterns, fixing
+    // one by one the bits that float here but not in `other`
+    fn subtract(&self, other: &Self) -> Vec<Self> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        let mut bits = self.floating & !other.floating;
+        while bits != 0 {
+            let bit = bits & bits.wrapping_neg();
+            bits &= bits - 1;
+            let floating = rest.floating & !bit;
+            pieces.push(Self::new(rest.value | (!other.value & bit), floating));
+            rest = Self::new(rest.value | (other.value & bit), floating);
+        }
+        pieces
+    }
+}
+
+// memory keeping every write as an address pattern, later writes cutting their
+// addresses out of the earlier ones so the patterns always stay disjoint
+pub struct SparseMemory {
+    writes: Vec<(AddressPattern, u64)>,
+}
+
+impl SparseMemory {
+    pub fn new() -> Self {
+        Self { writes: Vec::new() }
+    }
+
+    pub fn write(&mut self, pattern: AddressPattern, value: u64) {
+        self.writes = self
+            .writes
+            .iter()
+            .flat_map(|(written, val)| {
+                written
+                    .subtract(&pattern)
+                    .into_iter()
+                    .map(move |piece| (piece, *val))
+            })
+            .collect();
+        self.writes.push((pattern, value));
+    }
+
+    pub fn dump(&self) -> Vec<(AddressPattern, u64)> {
+        let mut dump = self.writes.clone();
+        dump.sort_unstable();
+        dump
+    }
+
+    pub fn sum(&self) -> u128 {
+        self.writes
+            .iter()
+            .map(|(pattern, val)| pattern.size() * *val as u128)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_writes_test() {
+        let all = (1 << 36) - 1;
+        let mut memory = SparseMemory::new();
+        memory.write(AddressPattern::new(0, all), 1);
+        memory.write(AddressPattern::new(0, all >> 6), 3);
+        memory.write(AddressPattern::new(1 << 35, all >> 6), 2);
+        assert_eq!(memory.sum(), (1 << 36) + 2 * (1 << 30) + (1 << 30));
+
+        memory.write(AddressPattern::new(5, 0), 10);
+        memory.write(AddressPattern::new(5, 0), 0);
+        assert_eq!(memory.sum(), (1 << 36) + 3 * (1 << 30) - 3);
+    }
+}
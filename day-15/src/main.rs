@@ -1,20 +1,19 @@
+mod van_eck;
+
+use van_eck::VanEck;
+
 fn main() {
     let data = [10, 16, 6, 0, 1, 17];
     println!("part1 solution: {:?}", get_nth_value(&data, 2020));
     println!("part2 solution: {:?}", get_nth_value(&data, 30000000));
 }
 
-fn get_nth_value(data: &[usize], n: usize) -> usize {
-    let mut values = vec![0; n];
-    for (i, &val) in data.iter().enumerate() {
-        values[val] = i + 1;
-    }
-    let mut prev = *data.last().expect("vec is empty");
-    for i in data.len()..n {
-        let v = std::mem::replace(&mut values[prev], i);
-        prev = if v == 0 { 0 } else { i - v };
-    }
-    prev
+// numbers spoken after the seeds are always smaller than the turn they're spoken
+// in, so only large seeds end up in the sparse part
+fn get_nth_value(data: &[u32], n: usize) -> u32 {
+    VanEck::new(data, n)
+        .nth(n - 1)
+        .expect("the game doesn't last that many turns")
 }
 
 #[cfg(test)]
@@ -26,4 +25,32 @@ mod tests {
         let data = [0, 3, 6];
         assert_eq!(get_nth_value(&data, 2020), 436);
     }
+
+    #[test]
+    fn seeds_test() {
+        assert_eq!(
+            VanEck::new(&[0, 3, 6], 10).take(10).collect::<Vec<_>>(),
+            vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0]
+        );
+        // the sparse part has to behave just like the dense one
+        assert_eq!(VanEck::new(&[0, 3, 6], 0).nth(2019), Some(436));
+        assert_eq!(
+            VanEck::new(&[4000000000, 1, 4000000000], 5)
+                .take(5)
+                .collect::<Vec<_>>(),
+            vec![4000000000, 1, 4000000000, 2, 0]
+        );
+    }
+
+    #[test]
+    fn analysis_test() {
+        let analysis = VanEck::new(&[0, 3, 6], 10).analyze(10, &[4, 1, 5, 4]);
+        assert_eq!(analysis.last, Some(0));
+        assert_eq!(analysis.first_turns, vec![Some(9), Some(7), None, Some(9)]);
+        assert_eq!(analysis.distinct, 5);
+        assert_eq!(
+            analysis.gaps.into_iter().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 1), (3, 2), (4, 1)]
+        );
+    }
 }
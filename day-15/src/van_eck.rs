@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, HashMap};
+
+// the turn every number was last spoken in, 0 meaning never. numbers below the
+// dense limit are looked up in an array, the rare larger ones in a map
+struct LastSpoken {
+    dense: Vec<u32>,
+    sparse: HashMap<u32, u32>,
+}
+
+impl LastSpoken {
+    fn new(dense_limit: usize) -> Self {
+        Self {
+            dense: vec![0; dense_limit],
+            sparse: HashMap::new(),
+        }
+    }
+
+    // records `turn` for `value`, returning the turn it was spoken in before
+    fn replace(&mut self, value: u32, turn: u32) -> u32 {
+        match self.dense.get_mut(value as usize) {
+            Some(last) => std::mem::replace(last, turn),
+            None => self.sparse.insert(value, turn).unwrap_or(0),
+        }
+    }
+}
+
+// lazily yields the spoken numbers, starting with the seeds. the game ends once
+// the turns no longer fit into u32
+pub struct VanEck {
+    seeds: Vec<u32>,
+    last_spoken: LastSpoken,
+    turn: u32,
+    next: u32,
+}
+
+impl VanEck {
+    pub fn new(seeds: &[u32], dense_limit: usize) -> Self {
+        Self {
+            seeds: seeds.to_vec(),
+            last_spoken: LastSpoken::new(dense_limit),
+            turn: 0,
+            next: 0,
+        }
+    }
+
+    // turns since the number spoken last was spoken before, None if it was new
+    #[allow(dead_code)]
+    pub fn last_gap(&self) -> Option<u32> {
+        match self.next {
+            0 => None,
+            gap => Some(gap),
+        }
+    }
+
+    // plays the next `turns` turns, collecting the analysis along the way. not
+    // needed for the answers, it's there for studying other seeds
+    #[allow(dead_code)]
+    pub fn analyze(mut self, turns: usize, queries: &[u32]) -> Analysis {
+        let mut analysis = Analysis {
+            last: None,
+            first_turns: vec![None; queries.len()],
+            distinct: 0,
+            gaps: BTreeMap::new(),
+        };
+        for _ in 0..turns {
+            let value = match self.next() {
+                Some(value) => value,
+                None => break,
+            };
+            match self.last_gap() {
+                Some(gap) => *analysis.gaps.entry(gap).or_insert(0) += 1,
+                None => {
+                    analysis.distinct += 1;
+                    queries
+                        .iter()
+                        .zip(analysis.first_turns.iter_mut())
+                        .filter(|(&query, _)| query == value)
+                        .for_each(|(_, first_turn)| *first_turn = Some(self.turn));
+                }
+            }
+            analysis.last = Some(value);
+        }
+        analysis
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq)]
+pub struct Analysis {
+    pub last: Option<u32>,
+    // turn (counting from 1) each queried number was first spoken in
+    pub first_turns: Vec<Option<u32>>,
+    pub distinct: usize,
+    // how many times a number got repeated after a gap of the given number of turns
+    pub gaps: BTreeMap<u32, usize>,
+}
+
+impl Iterator for VanEck {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let turn = self.turn.checked_add(1)?;
+        let value = match self.seeds.get(self.turn as usize) {
+            Some(&seed) => seed,
+            None => self.next,
+        };
+        let last = self.last_spoken.replace(value, turn);
+        self.next = if last == 0 { 0 } else { turn - last };
+        self.turn = turn;
+        Some(value)
+    }
+}
@@ -0,0 +1,180 @@
+use crate::Rule;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSet {
+    words: Vec<u64>,
+}
+
+impl RuleSet {
+    pub fn empty(rules: usize) -> Self {
+        Self {
+            words: vec![0; rules.div_ceil(64)],
+        }
+    }
+
+    pub fn full(rules: usize) -> Self {
+        let mut set = Self::empty(rules);
+        (0..rules).for_each(|rule| set.insert(rule));
+        set
+    }
+
+    pub fn insert(&mut self, rule: usize) {
+        self.words[rule / 64] |= 1 << (rule % 64);
+    }
+
+    pub fn contains(&self, rule: usize) -> bool {
+        self.words[rule / 64] & (1 << (rule % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    // rules in this set but not in `other`
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            words: self
+                .words
+                .iter()
+                .zip(other.words.iter())
+                .map(|(word, other)| word & !other)
+                .collect(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            let mut bits = word;
+            std::iter::from_fn(move || {
+                if bits == 0 {
+                    return None;
+                }
+                let bit = bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.words
+            .iter_mut()
+            .zip(other.words.iter())
+            .for_each(|(word, other)| *word &= other);
+    }
+}
+
+// all rule ranges merged into disjoint segments, each knowing the rules that
+// accept its values. segment i starts at starts[i] and ends where the next one starts
+pub struct RuleIndex {
+    rules: usize,
+    starts: Vec<usize>,
+    sets: Vec<RuleSet>,
+}
+
+impl RuleIndex {
+    pub fn new(rules: &[Rule]) -> Self {
+        // every range opens at its start and closes right after its end
+        let mut events = rules
+            .iter()
+            .enumerate()
+            .flat_map(|(rule, r)| {
+                r.ranges.iter().flat_map(move |range| {
+                    let close = range.end().checked_add(1).map(|end| (end, rule, false));
+                    std::iter::once((*range.start(), rule, true)).chain(close)
+                })
+            })
+            .collect::<Vec<_>>();
+        events.sort_unstable();
+
+        let mut open = vec![0; rules.len()];
+        let mut index = Self {
+            rules: rules.len(),
+            starts: vec![0],
+            sets: vec![RuleSet::empty(rules.len())],
+        };
+        let mut i = 0;
+        while i < events.len() {
+            let point = events[i].0;
+            while i < events.len() && events[i].0 == point {
+                let (_, rule, opens) = events[i];
+                if opens {
+                    open[rule] += 1;
+                } else {
+                    open[rule] -= 1;
+                }
+                i += 1;
+            }
+            let mut set = RuleSet::empty(rules.len());
+            (0..rules.len())
+                .filter(|&rule| open[rule] > 0)
+                .for_each(|rule| set.insert(rule));
+            // only ranges starting at 0 replace the initial segment
+            if index.starts.last() == Some(&point) {
+                index.starts.pop();
+                index.sets.pop();
+            }
+            if index.sets.last() != Some(&set) {
+                index.starts.push(point);
+                index.sets.push(set);
+            }
+        }
+        index
+    }
+
+    pub fn rule_count(&self) -> usize {
+        self.rules
+    }
+
+    pub fn accepting(&self, value: usize) -> &RuleSet {
+        &self.sets[self.starts.partition_point(|&start| start <= value) - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_test() {
+        let rules = vec![
+            Rule::new("class".into(), vec![1..=3, 5..=7]),
+            Rule::new("row".into(), vec![6..=11, 33..=44]),
+            Rule::new("seat".into(), vec![13..=40, 45..=50]),
+        ];
+        let index = RuleIndex::new(&rules);
+        let accepting = |value| {
+            (0..3)
+                .filter(|&rule| index.accepting(value).contains(rule))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(accepting(0), []);
+        assert_eq!(accepting(3), [0]);
+        assert_eq!(accepting(4), []);
+        assert_eq!(accepting(6), [0, 1]);
+        assert_eq!(accepting(12), []);
+        assert_eq!(accepting(35), [1, 2]);
+        assert_eq!(accepting(50), [2]);
+        assert_eq!(accepting(usize::MAX), []);
+
+        let rules = (0..100)
+            .map(|i| Rule::new(i.to_string(), vec![i..=i + 10, 0..=0]))
+            .collect::<Vec<_>>();
+        let index = RuleIndex::new(&rules);
+        assert_eq!(
+            (0..100)
+                .filter(|&rule| index.accepting(50).contains(rule))
+                .collect::<Vec<_>>(),
+            (40..=50).collect::<Vec<_>>()
+        );
+        assert_eq!(*index.accepting(0), RuleSet::full(100));
+        assert_eq!(
+            index
+                .accepting(50)
+                .difference(index.accepting(45))
+                .iter()
+                .collect::<Vec<_>>(),
+            (46..=50).collect::<Vec<_>>()
+        );
+    }
+}
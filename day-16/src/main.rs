@@ -1,4 +1,11 @@
-use std::collections::{BTreeMap, HashSet, VecDeque};
+mod index;
+mod report;
+mod solver;
+
+use index::RuleIndex;
+use report::ValidationReport;
+use solver::{SolveError, Solver};
+use std::collections::BTreeMap;
 use std::fs::read_to_string;
 use std::ops::RangeInclusive;
 
@@ -6,84 +13,51 @@ fn main() {
     let data = read("input.txt");
     println!(
         "part1 solution: {:?}",
-        count_invalid_values_in_other_tickets(&data)
+        count_invalid_values_in_other_tickets(&data).expect("Failed to validate tickets")
+    );
+    println!(
+        "part2 solution: {:?}",
+        calculate_departure_value(&data).expect("Failed to order fields")
     );
-    println!("part2 solution: {:?}", calculate_departure_value(&data));
 }
 
-fn find_fields_order(data: &Data) -> BTreeMap<usize, usize> {
-    let valid_tickets = filter_tickets(&data, true);
-    let mut candidates = (0..data.rules.len())
-        .map(|i| {
-            (0..data.rules.len())
-                .filter(|&j| {
-                    valid_tickets
-                        .iter()
-                        .all(|ticket| data.rules[j].is_valid(&ticket[i]))
-                })
-                .collect::<HashSet<usize>>()
-        })
-        .enumerate()
-        .collect::<VecDeque<(usize, HashSet<usize>)>>();
+fn solver_for(data: &Data) -> Result<Solver, SolveError> {
+    let valid_tickets = ValidationReport::new(data)?
+        .valid
+        .into_iter()
+        .map(|i| (i, data.other_tickets[i].as_slice()))
+        .collect::<Vec<_>>();
+    Solver::new(&data.index, data.my_ticket.len(), &valid_tickets)
+}
 
-    let mut order = BTreeMap::new();
-    while !candidates.is_empty() {
-        candidates.rotate_left(
-            candidates
-                .iter()
-                .position(|(_, set)| set.len() == 1)
-                .unwrap(),
-        );
-        if let Some((new_pos, curr_set)) = candidates.pop_front() {
-            let orig_pos = *curr_set.iter().next().unwrap();
-            order.insert(new_pos, orig_pos);
-            candidates.iter_mut().for_each(|(_, set)| {
-                set.remove(&orig_pos);
-            });
-        }
-    }
-    order
+fn find_fields_order(data: &Data) -> Result<BTreeMap<usize, usize>, SolveError> {
+    let order = solver_for(data)?.solve()?;
+    Ok(order.into_iter().enumerate().collect())
 }
 
-fn calculate_departure_value(data: &Data) -> usize {
-    find_fields_order(&data)
+// field names of my ticket along with their values
+fn decode_my_ticket(data: &Data) -> Result<BTreeMap<String, usize>, SolveError> {
+    Ok(find_fields_order(data)?
         .iter()
-        .filter_map(|(&new_pos, &orig_pos)| {
-            if data.rules[orig_pos].name.starts_with("departure") {
-                Some(data.my_ticket[new_pos])
-            } else {
-                None
-            }
-        })
-        .product()
+        .map(|(&new_pos, &orig_pos)| (data.rules[orig_pos].name.clone(), data.my_ticket[new_pos]))
+        .collect())
 }
 
-fn count_invalid_values_in_other_tickets(data: &Data) -> usize {
-    let invalid_tickets = filter_tickets(&data, false);
-    invalid_tickets
+fn calculate_departure_value(data: &Data) -> Result<usize, SolveError> {
+    Ok(decode_my_ticket(data)?
         .iter()
-        .map(|vals| {
-            vals.iter()
-                .filter(|v| !data.rules.iter().any(|rule| rule.is_valid(v)))
-                .sum::<usize>()
-        })
-        .sum()
+        .filter(|(name, _)| name.starts_with("departure"))
+        .map(|(_, value)| value)
+        .product())
 }
 
-fn filter_tickets(data: &Data, is_valid: bool) -> Vec<Vec<usize>> {
-    data.other_tickets
-        .iter()
-        .filter(|vals| {
-            vals.iter()
-                .any(|v| !data.rules.iter().any(|rule| rule.is_valid(v)))
-                ^ is_valid
-        })
-        .cloned()
-        .collect::<Vec<Vec<usize>>>()
+fn count_invalid_values_in_other_tickets(data: &Data) -> Result<usize, SolveError> {
+    Ok(ValidationReport::new(data)?.error_rate())
 }
 
 struct Data {
     rules: Vec<Rule>,
+    index: RuleIndex,
     my_ticket: Vec<usize>,
     other_tickets: Vec<Vec<usize>>,
 }
@@ -96,16 +70,12 @@ impl Rule {
     pub fn new(name: String, ranges: Vec<RangeInclusive<usize>>) -> Self {
         Self { name, ranges }
     }
-
-    pub fn is_valid(&self, value: &usize) -> bool {
-        self.ranges.iter().any(|r| r.contains(&value))
-    }
 }
 
 fn read(filename: &str) -> Data {
     let content = read_to_string(filename).expect("Failed to read file");
     let parts = content.split_terminator("\n\n").collect::<Vec<&str>>();
-    let rules = parts[0]
+    let rules: Vec<Rule> = parts[0]
         .lines()
         .map(|s| {
             let s_parts = s.split_terminator(": ").collect::<Vec<&str>>();
@@ -141,6 +111,7 @@ fn read(filename: &str) -> Data {
         .collect();
 
     Data {
+        index: RuleIndex::new(&rules),
         rules,
         my_ticket,
         other_tickets,
@@ -154,17 +125,23 @@ mod tests {
     #[test]
     fn test1() {
         let data = read("test-input.txt");
-        assert_eq!(count_invalid_values_in_other_tickets(&data), 71);
-        let valid_tickets = filter_tickets(&data, true);
-        assert_eq!(valid_tickets, [[7, 3, 47]]);
-        let invalid_tickets = filter_tickets(&data, false);
-        assert_eq!(invalid_tickets, [[40, 4, 50], [55, 2, 20], [38, 6, 12]]);
+        assert_eq!(count_invalid_values_in_other_tickets(&data), Ok(71));
+        let report = ValidationReport::new(&data).unwrap();
+        assert_eq!(report.valid, [0]);
+        assert_eq!(
+            report
+                .rejected
+                .iter()
+                .map(|rejection| (rejection.ticket, rejection.values.clone()))
+                .collect::<Vec<_>>(),
+            [(1, vec![4]), (2, vec![55]), (3, vec![12])]
+        );
     }
 
     #[test]
     fn test2() {
         let data = read("test-input2.txt");
-        let order = find_fields_order(&data);
+        let order = find_fields_order(&data).unwrap();
         assert_eq!(
             order
                 .values()
@@ -175,6 +152,40 @@ mod tests {
                 .map(|s| s.to_string())
                 .collect::<Vec<String>>()
         );
-        assert_eq!(calculate_departure_value(&data), 11 * 13);
+        assert_eq!(calculate_departure_value(&data), Ok(11 * 13));
+        assert_eq!(
+            ValidationReport::new(&data).unwrap().coverage,
+            [
+                [false, true, false],
+                [true, true, false],
+                [true, true, true]
+            ]
+        );
+        let decoded = decode_my_ticket(&data).unwrap();
+        assert_eq!(decoded["class"], 12);
+        assert_eq!(decoded["departure row"], 11);
+        assert_eq!(decoded["departure seat"], 13);
+    }
+
+    #[test]
+    fn ragged_tickets_test() {
+        let mut data = read("test-input2.txt");
+        data.other_tickets[1] = vec![15, 1];
+        assert_eq!(
+            ValidationReport::new(&data),
+            Err(SolveError::RaggedTicket { ticket: 1 })
+        );
+        assert_eq!(
+            decode_my_ticket(&data),
+            Err(SolveError::RaggedTicket { ticket: 1 })
+        );
+
+        // my ticket being the short one makes every nearby ticket ragged
+        let mut data = read("test-input2.txt");
+        data.my_ticket.pop();
+        assert_eq!(
+            calculate_departure_value(&data),
+            Err(SolveError::RaggedTicket { ticket: 0 })
+        );
     }
 }
@@ -0,0 +1,70 @@
+use crate::index::RuleSet;
+use crate::solver::SolveError;
+use crate::Data;
+
+#[derive(Debug, PartialEq)]
+pub struct Rejection {
+    pub ticket: usize,
+    // the values no rule accepts
+    pub values: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ValidationReport {
+    pub valid: Vec<usize>,
+    pub rejected: Vec<Rejection>,
+    // for every column, whether each rule accepts all of its values on the valid tickets
+    pub coverage: Vec<Vec<bool>>,
+}
+
+impl ValidationReport {
+    // every nearby ticket has to have as many values as mine
+    pub fn new(data: &Data) -> Result<Self, SolveError> {
+        let columns = data.my_ticket.len();
+        if let Some(ticket) = data
+            .other_tickets
+            .iter()
+            .position(|values| values.len() != columns)
+        {
+            return Err(SolveError::RaggedTicket { ticket });
+        }
+        let (mut valid, mut rejected) = (Vec::new(), Vec::new());
+        for (ticket, values) in data.other_tickets.iter().enumerate() {
+            let invalid = values
+                .iter()
+                .copied()
+                .filter(|&v| data.index.accepting(v).is_empty())
+                .collect::<Vec<_>>();
+            if invalid.is_empty() {
+                valid.push(ticket);
+            } else {
+                rejected.push(Rejection {
+                    ticket,
+                    values: invalid,
+                });
+            }
+        }
+        let rules = data.rules.len();
+        let coverage = (0..columns)
+            .map(|column| {
+                let accepting = valid.iter().fold(RuleSet::full(rules), |mut acc, &ticket| {
+                    acc.intersect_with(data.index.accepting(data.other_tickets[ticket][column]));
+                    acc
+                });
+                (0..rules).map(|rule| accepting.contains(rule)).collect()
+            })
+            .collect();
+        Ok(Self {
+            valid,
+            rejected,
+            coverage,
+        })
+    }
+
+    pub fn error_rate(&self) -> usize {
+        self.rejected
+            .iter()
+            .flat_map(|rejection| rejection.values.iter())
+            .sum()
+    }
+}
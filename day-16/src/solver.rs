@@ -0,0 +1,365 @@
+use crate::index::{RuleIndex, RuleSet};
+use std::collections::VecDeque;
+
+// more valid assignments than this aren't listed when reporting ambiguity
+const MAX_ASSIGNMENTS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    // the value at this column of the nearby ticket isn't accepted by the rule
+    Ticket { ticket: usize, value: usize },
+    // the rule already belongs to another column
+    Assigned { column: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Elimination {
+    pub column: usize,
+    pub rule: usize,
+    pub reason: Reason,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SolveError {
+    // a set of columns accepting fewer rules between them than there are columns,
+    // so they can't all get a rule of their own in any assignment
+    Unsatisfiable { columns: Vec<usize> },
+    // every assignment that fits the tickets, up to MAX_ASSIGNMENTS of them
+    Ambiguous { assignments: Vec<Vec<usize>> },
+    // the ticket has a different number of values than there are columns
+    RaggedTicket { ticket: usize },
+}
+
+// assigns a distinct rule to every ticket column, first by eliminating the
+// candidates of columns left with a single rule and then by bipartite matching
+pub struct Solver {
+    rules: usize,
+    candidates: Vec<Vec<usize>>,
+    eliminations: Vec<Elimination>,
+}
+
+impl Solver {
+    // tickets are given along with their index, which is used in the explanations,
+    // and have to hold a value for each of the `columns`
+    pub fn new(
+        index: &RuleIndex,
+        columns: usize,
+        tickets: &[(usize, &[usize])],
+    ) -> Result<Self, SolveError> {
+        let rules = index.rule_count();
+        if let Some(&(ticket, _)) = tickets.iter().find(|(_, values)| values.len() != columns) {
+            return Err(SolveError::RaggedTicket { ticket });
+        }
+        let mut remaining = vec![RuleSet::full(rules); columns];
+        let mut eliminations = Vec::new();
+        for &(ticket, values) in tickets {
+            for (column, remaining) in remaining.iter_mut().enumerate() {
+                let accepting = index.accepting(values[column]);
+                let rejected = remaining.difference(accepting);
+                if rejected.is_empty() {
+                    continue;
+                }
+                for rule in rejected.iter() {
+                    eliminations.push(Elimination {
+                        column,
+                        rule,
+                        reason: Reason::Ticket {
+                            ticket,
+                            value: values[column],
+                        },
+                    });
+                }
+                remaining.intersect_with(accepting);
+            }
+        }
+        eliminations.sort_by_key(|elimination| (elimination.column, elimination.rule));
+        let candidates = remaining.iter().map(|set| set.iter().collect()).collect();
+        Ok(Self {
+            rules,
+            candidates,
+            eliminations,
+        })
+    }
+
+    // why every candidate got dropped, in the order of the columns
+    #[allow(dead_code)]
+    pub fn eliminations(&self) -> &[Elimination] {
+        &self.eliminations
+    }
+
+    // returns the rule for every column
+    pub fn solve(&mut self) -> Result<Vec<usize>, SolveError> {
+        // checked ahead of propagation, which would otherwise pick one of the
+        // competing columns to keep the rule
+        let violating = hall_violator(&self.candidates, self.rules);
+        if !violating.is_empty() {
+            return Err(SolveError::Unsatisfiable { columns: violating });
+        }
+        self.propagate();
+
+        let mut assignments = Vec::new();
+        find_assignments(&self.candidates, self.rules, &mut assignments);
+        match assignments.len() {
+            1 => Ok(assignments.pop().unwrap()),
+            _ => Err(SolveError::Ambiguous { assignments }),
+        }
+    }
+
+    // removes the rule of every column with a single candidate from all the others
+    fn propagate(&mut self) {
+        let mut settled = vec![false; self.candidates.len()];
+        while let Some(column) =
+            (0..self.candidates.len()).find(|&c| !settled[c] && self.candidates[c].len() == 1)
+        {
+            settled[column] = true;
+            let rule = self.candidates[column][0];
+            for (other, candidates) in self.candidates.iter_mut().enumerate() {
+                if other != column && candidates.contains(&rule) {
+                    candidates.retain(|&r| r != rule);
+                    self.eliminations.push(Elimination {
+                        column: other,
+                        rule,
+                        reason: Reason::Assigned { column },
+                    });
+                }
+            }
+        }
+    }
+}
+
+// collects every perfect matching, trying the candidates of the least
+// constrained column in turn and dropping branches that can't be completed
+fn find_assignments(candidates: &[Vec<usize>], rules: usize, found: &mut Vec<Vec<usize>>) {
+    if found.len() >= MAX_ASSIGNMENTS
+        || hopcroft_karp(candidates, rules)
+            .iter()
+            .any(|rule| rule.is_none())
+    {
+        return;
+    }
+    let open = (0..candidates.len())
+        .filter(|&column| candidates[column].len() > 1)
+        .min_by_key(|&column| candidates[column].len());
+    match open {
+        None => found.push(candidates.iter().map(|rules| rules[0]).collect()),
+        Some(column) => {
+            for &rule in candidates[column].iter() {
+                let fixed = candidates
+                    .iter()
+                    .enumerate()
+                    .map(|(other, rules)| {
+                        if other == column {
+                            vec![rule]
+                        } else {
+                            rules.iter().copied().filter(|&r| r != rule).collect()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                find_assignments(&fixed, rules, found);
+            }
+        }
+    }
+}
+
+// maximum matching between columns and the rules they accept, returning the
+// rule matched to every column
+fn hopcroft_karp(candidates: &[Vec<usize>], rules: usize) -> Vec<Option<usize>> {
+    let mut matching = Matching {
+        candidates,
+        column_rule: vec![None; candidates.len()],
+        rule_column: vec![None; rules],
+        dist: vec![usize::MAX; candidates.len()],
+    };
+    while matching.layer() {
+        for column in 0..candidates.len() {
+            if matching.column_rule[column].is_none() {
+                matching.augment(column);
+            }
+        }
+    }
+    matching.column_rule
+}
+
+// columns reachable from the unmatched ones of a maximum matching by alternating
+// paths. their rules are all matched within the set, which holds one column more
+// than that for every unmatched column, so no assignment can cover them all
+fn hall_violator(candidates: &[Vec<usize>], rules: usize) -> Vec<usize> {
+    let column_rule = hopcroft_karp(candidates, rules);
+    let mut rule_column = vec![None; rules];
+    for (column, rule) in column_rule.iter().enumerate() {
+        if let Some(rule) = rule {
+            rule_column[*rule] = Some(column);
+        }
+    }
+    let mut reached = column_rule
+        .iter()
+        .map(|rule| rule.is_none())
+        .collect::<Vec<_>>();
+    let mut queue = (0..candidates.len())
+        .filter(|&column| reached[column])
+        .collect::<VecDeque<_>>();
+    while let Some(column) = queue.pop_front() {
+        for &rule in candidates[column].iter() {
+            if let Some(next) = rule_column[rule] {
+                if !reached[next] {
+                    reached[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    (0..candidates.len())
+        .filter(|&column| reached[column])
+        .collect()
+}
+
+struct Matching<'a> {
+    candidates: &'a [Vec<usize>],
+    column_rule: Vec<Option<usize>>,
+    rule_column: Vec<Option<usize>>,
+    dist: Vec<usize>,
+}
+
+impl Matching<'_> {
+    // layers the columns by their distance from the unmatched ones, returning
+    // whether an augmenting path exists
+    fn layer(&mut self) -> bool {
+        let mut queue = VecDeque::new();
+        for (column, rule) in self.column_rule.iter().enumerate() {
+            self.dist[column] = match rule {
+                None => {
+                    queue.push_back(column);
+                    0
+                }
+                Some(_) => usize::MAX,
+            };
+        }
+        let mut found = false;
+        while let Some(column) = queue.pop_front() {
+            for &rule in self.candidates[column].iter() {
+                match self.rule_column[rule] {
+                    None => found = true,
+                    Some(next) if self.dist[next] == usize::MAX => {
+                        self.dist[next] = self.dist[column] + 1;
+                        queue.push_back(next);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        found
+    }
+
+    fn augment(&mut self, column: usize) -> bool {
+        for &rule in self.candidates[column].iter() {
+            let free = match self.rule_column[rule] {
+                None => true,
+                Some(next) => self.dist[next] == self.dist[column] + 1 && self.augment(next),
+            };
+            if free {
+                self.column_rule[column] = Some(rule);
+                self.rule_column[rule] = Some(column);
+                return true;
+            }
+        }
+        self.dist[column] = usize::MAX;
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rule;
+
+    #[test]
+    fn matching_test() {
+        // no column is left with a single candidate, so only matching helps
+        let candidates = vec![vec![0, 1], vec![1, 2], vec![0, 2], vec![3]];
+        let mut found = Vec::new();
+        find_assignments(&candidates, 4, &mut found);
+        assert_eq!(found, vec![vec![0, 1, 2, 3], vec![1, 2, 0, 3]]);
+
+        let candidates = vec![vec![0, 1], vec![0], vec![1], vec![2]];
+        assert_eq!(
+            hopcroft_karp(&candidates, 3)
+                .iter()
+                .filter(|rule| rule.is_none())
+                .count(),
+            1
+        );
+        // both columns compete for the one rule, neither is the culprit alone
+        assert_eq!(hall_violator(&[vec![0], vec![0]], 1), vec![0, 1]);
+        assert_eq!(
+            hall_violator(&[vec![0, 1], vec![0], vec![1], vec![2]], 3),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn explanation_test() {
+        let rules = vec![
+            Rule::new("a".into(), vec![1..=5]),
+            Rule::new("b".into(), vec![1..=10]),
+            Rule::new("c".into(), vec![10..=20]),
+        ];
+        let tickets = [(0, &[3, 7, 15][..]), (2, &[4, 12, 10][..])];
+        let mut solver = Solver::new(&RuleIndex::new(&rules), 3, &tickets).unwrap();
+        assert_eq!(
+            solver.solve(),
+            Err(SolveError::Unsatisfiable { columns: vec![1] })
+        );
+        assert_eq!(
+            solver.eliminations()[..3],
+            [
+                Elimination {
+                    column: 0,
+                    rule: 2,
+                    reason: Reason::Ticket {
+                        ticket: 0,
+                        value: 3
+                    }
+                },
+                Elimination {
+                    column: 1,
+                    rule: 0,
+                    reason: Reason::Ticket {
+                        ticket: 0,
+                        value: 7
+                    }
+                },
+                Elimination {
+                    column: 1,
+                    rule: 1,
+                    reason: Reason::Ticket {
+                        ticket: 2,
+                        value: 12
+                    }
+                },
+            ]
+        );
+
+        let ragged = [(0, &[3, 7, 15][..]), (4, &[4, 12][..])];
+        assert_eq!(
+            Solver::new(&RuleIndex::new(&rules), 3, &ragged).err(),
+            Some(SolveError::RaggedTicket { ticket: 4 })
+        );
+
+        // both columns only accept c, and propagation mustn't settle it on either
+        let tickets = [(0, &[15, 20][..])];
+        let mut solver = Solver::new(&RuleIndex::new(&rules), 2, &tickets).unwrap();
+        assert_eq!(
+            solver.solve(),
+            Err(SolveError::Unsatisfiable {
+                columns: vec![0, 1]
+            })
+        );
+
+        // without tickets every column still accepts every rule
+        let mut solver = Solver::new(&RuleIndex::new(&rules), 2, &[]).unwrap();
+        match solver.solve() {
+            Err(SolveError::Ambiguous { assignments }) => assert_eq!(assignments.len(), 6),
+            result => panic!("unexpected {:?}", result),
+        }
+    }
+}
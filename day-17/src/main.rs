@@ -1,6 +1,5 @@
-#![feature(min_const_generics)] // Not a stable feature yet => requires nightly
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use automaton::{Automaton, Lattice, Rules};
+use std::collections::HashSet;
 use std::fs::read_to_string;
 
 fn main() {
@@ -11,41 +10,10 @@ fn main() {
     println!("part2 solution: {}", count(data));
 }
 
-fn count<const N: usize>(mut active: HashSet<Point<N>>) -> usize {
-    let diffs = generate_diffs::<N>();
-    (0..6).for_each(|_| {
-        let counts = active.iter().fold(HashMap::new(), |mut acc, pos| {
-            diffs.iter().for_each(|diff| {
-                *acc.entry(add(pos, diff)).or_insert(0) += 1;
-            });
-            acc
-        });
-        active = counts
-            .iter()
-            .filter(|&(pos, &count)| count == 3 || active.contains(pos) && count == 2)
-            .map(|(pos, _)| *pos)
-            .collect();
-    });
-
-    active.len()
-}
-
-fn generate_diffs<const N: usize>() -> Vec<Point<N>> {
-    (0..N)
-        .map(|_i| -1..=1)
-        .multi_cartesian_product()
-        .filter_map(convert)
-        .collect()
-}
-
-fn convert<const N: usize>(v: Vec<i32>) -> Option<Point<N>> {
-    let boxed = v.into_boxed_slice();
-    let mut a = [0; N];
-    a.copy_from_slice(&boxed[0..N]);
-    if a == [0; N] {
-        return None;
-    }
-    Some(a)
+fn count<const N: usize>(active: HashSet<Point<N>>) -> usize {
+    let mut automaton = Automaton::new(Lattice::<N>::new(), Rules::life(), active);
+    automaton.run(6);
+    automaton.population()
 }
 
 fn read<const N: usize>(filename: &str) -> HashSet<Point<N>> {
@@ -69,11 +37,6 @@ fn read<const N: usize>(filename: &str) -> HashSet<Point<N>> {
 }
 
 type Point<const N: usize> = [i32; N];
-fn add<const N: usize>(pos: &Point<N>, diff: &Point<N>) -> Point<N> {
-    let mut res = *pos;
-    res.iter_mut().zip(diff.iter()).for_each(|(a, &b)| *a += b);
-    res
-}
 
 #[cfg(test)]
 mod tests {
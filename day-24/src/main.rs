@@ -1,3 +1,4 @@
+use automaton::{Automaton, Hex};
 use std::collections::HashMap;
 use std::fs::read_to_string;
 
@@ -45,31 +46,17 @@ fn flip_tiles(instructions: &[Vec<Direction>]) -> HashMap<Point, Color> {
     map
 }
 
-fn run(tiles: &HashMap<Point, Color>) -> usize {
-    let mut current = tiles.clone();
-    for _i in 0..100 {
-        let mut counts = HashMap::new();
-        current.iter().for_each(|(pos, color)| {
-            if color == &Color::Black {
-                for p in get_neighbour_positions(*pos) {
-                    *counts.entry(p).or_insert(0) += 1;
-                }
-            }
-        });
-        current = counts
-            .iter()
-            .filter(|&(tile_pos, &count)| {
-                let tile_color = current.get(tile_pos).unwrap_or(&Color::White);
-                tile_color == &Color::Black && count == 1 || count == 2
-            })
-            .map(|tile| (*tile.0, Color::Black))
-            .collect();
-    }
-    current.len()
-}
+// a black tile stays black with 1 or 2 black neighbours, a white one turns black with 2
+const FLIP_RULES: &str = "B2/S12";
 
-fn get_neighbour_positions(pos: Point) -> Vec<Point> {
-    DIFFS.iter().map(|&diff| pos.add(diff)).collect()
+fn run(tiles: &HashMap<Point, Color>) -> usize {
+    let black = tiles
+        .iter()
+        .filter(|&(_, color)| color == &Color::Black)
+        .map(|(pos, _)| (pos.x, pos.y, pos.z));
+    let mut automaton = Automaton::new(Hex::new(), FLIP_RULES.parse().unwrap(), black);
+    automaton.run(100);
+    automaton.population()
 }
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]